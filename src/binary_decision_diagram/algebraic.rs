@@ -0,0 +1,517 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use petgraph::{dot::Dot, stable_graph::NodeIndex};
+use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
+
+use super::manager::VariableId;
+use super::node_store::{Node, NodeStore};
+use super::{BinaryDecisionDiagram, Manager, VariableOrder};
+
+thread_local! {
+    static DEFAULT_MANAGER: Rc<RefCell<AlgebraicManager>> =
+        Rc::new(RefCell::new(AlgebraicManager::new()));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Operation {
+    Plus,
+    Times,
+    Max,
+    Min,
+}
+
+impl Operation {
+    fn eval(self, lhs: f64, rhs: f64) -> f64 {
+        match self {
+            Operation::Plus => lhs + rhs,
+            Operation::Times => lhs * rhs,
+            Operation::Max => lhs.max(rhs),
+            Operation::Min => lhs.min(rhs),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum CacheKey {
+    // Every operation is commutative, so the operands are stored in ascending order.
+    Apply(Operation, NodeIndex, NodeIndex),
+    Restrict(NodeIndex, VariableId, bool),
+}
+
+// Owns the nodes of algebraic decision diagrams, whose terminals are numbers.
+// Terminals are unique by their bits, after merging `-0.0` into `0.0`.
+// These diagrams are never reordered, so a variable's id is also its level.
+struct AlgebraicManager {
+    store: NodeStore<f64>,
+    order: VariableOrder,
+    computed_cache: HashMap<CacheKey, NodeIndex>,
+}
+
+impl AlgebraicManager {
+    fn new() -> Self {
+        Self {
+            store: NodeStore::new(),
+            order: VariableOrder::default(),
+            computed_cache: HashMap::new(),
+        }
+    }
+
+    fn constant(&mut self, value: f64) -> NodeIndex {
+        self.store.terminal(value + 0.0)
+    }
+
+    fn variable(&mut self, name: &str) -> NodeIndex {
+        let id = self.order.push(name);
+        let false_child = self.constant(0.0);
+        let true_child = self.constant(1.0);
+        self.make_node(id, false_child, true_child)
+    }
+
+    fn level(&self, node: NodeIndex) -> usize {
+        match self.store.node(node) {
+            Node::Terminal(_) => usize::MAX,
+            Node::Variable(id) => id,
+        }
+    }
+
+    fn label(&self, node: NodeIndex) -> String {
+        match self.store.node(node) {
+            Node::Terminal(value) => value.to_string(),
+            Node::Variable(id) => self.order.variable_at(id).to_string(),
+        }
+    }
+
+    fn make_node(
+        &mut self,
+        variable: VariableId,
+        false_child: NodeIndex,
+        true_child: NodeIndex,
+    ) -> NodeIndex {
+        if false_child == true_child {
+            return false_child;
+        }
+        debug_assert!(variable < self.level(false_child) && variable < self.level(true_child));
+        self.store.make_node(variable, false_child, true_child)
+    }
+
+    // Removes every node unreachable from a referenced root and returns how many were removed.
+    fn collect_garbage(&mut self) -> usize {
+        let result = self.store.collect_garbage();
+        self.computed_cache.clear();
+        result
+    }
+
+    // Called between operations, when every node in use is held by a referenced root.
+    fn collect_garbage_if_needed(&mut self) {
+        if self.store.needs_collection() {
+            self.collect_garbage();
+        }
+    }
+
+    fn cofactors_at(&self, node: NodeIndex, level: usize) -> (NodeIndex, NodeIndex) {
+        if self.level(node) == level {
+            self.store.children(node)
+        } else {
+            (node, node)
+        }
+    }
+
+    fn apply(&mut self, operation: Operation, lhs: NodeIndex, rhs: NodeIndex) -> NodeIndex {
+        if let (Some(lhs), Some(rhs)) = (
+            self.store.terminal_value(lhs),
+            self.store.terminal_value(rhs),
+        ) {
+            return self.constant(operation.eval(lhs, rhs));
+        }
+        let key = CacheKey::Apply(operation, lhs.min(rhs), lhs.max(rhs));
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let level = self.level(lhs).min(self.level(rhs));
+        let (lhs_false, lhs_true) = self.cofactors_at(lhs, level);
+        let (rhs_false, rhs_true) = self.cofactors_at(rhs, level);
+        let false_child = self.apply(operation, lhs_false, rhs_false);
+        let true_child = self.apply(operation, lhs_true, rhs_true);
+        let result = self.make_node(level, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    fn restrict(&mut self, node: NodeIndex, variable: &str, value: bool) -> NodeIndex {
+        let Some(level) = self.order.level(variable) else {
+            return node;
+        };
+        self.restrict_level(node, level, value)
+    }
+
+    fn restrict_level(&mut self, node: NodeIndex, level: usize, value: bool) -> NodeIndex {
+        let node_level = self.level(node);
+        if node_level > level {
+            return node;
+        }
+        let (false_child, true_child) = self.store.children(node);
+        if node_level == level {
+            return if value { true_child } else { false_child };
+        }
+        let key = CacheKey::Restrict(node, level, value);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let false_child = self.restrict_level(false_child, level, value);
+        let true_child = self.restrict_level(true_child, level, value);
+        let result = self.make_node(node_level, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+}
+
+// A function from assignments to numbers, such as a cost or a probability per assignment.
+// Nodes are shared by every diagram of the thread.
+#[wasm_bindgen]
+pub struct AlgebraicDecisionDiagram {
+    manager: Rc<RefCell<AlgebraicManager>>,
+    root: NodeIndex,
+}
+
+impl Clone for AlgebraicDecisionDiagram {
+    fn clone(&self) -> Self {
+        self.manager.borrow_mut().store.reference(self.root);
+        Self {
+            manager: self.manager.clone(),
+            root: self.root,
+        }
+    }
+}
+
+impl Drop for AlgebraicDecisionDiagram {
+    fn drop(&mut self) {
+        if let Ok(mut manager) = self.manager.try_borrow_mut() {
+            manager.store.dereference(self.root);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl AlgebraicDecisionDiagram {
+    pub fn constant(value: f64) -> Self {
+        let manager = DEFAULT_MANAGER.with(|it| it.clone());
+        let root = manager.borrow_mut().constant(value);
+        Self::from_root(manager, root)
+    }
+
+    // 1 where `name` is true, 0 elsewhere.
+    pub fn variable(name: &str) -> Self {
+        let manager = DEFAULT_MANAGER.with(|it| it.clone());
+        let root = manager.borrow_mut().variable(name);
+        Self::from_root(manager, root)
+    }
+
+    // 1 where `diagram` holds, 0 elsewhere.
+    pub fn from_binary_decision_diagram(diagram: &BinaryDecisionDiagram) -> Self {
+        fn convert(
+            manager: &mut AlgebraicManager,
+            source: &Manager,
+            node: NodeIndex,
+            converted: &mut HashMap<NodeIndex, NodeIndex>,
+        ) -> NodeIndex {
+            if let Some(value) = source.terminal_value(node) {
+                return manager.constant(value as u8 as f64);
+            }
+            if let Some(result) = converted.get(&node) {
+                return *result;
+            }
+            let (false_child, true_child) = source.children(node);
+            let false_child = convert(manager, source, false_child, converted);
+            let true_child = convert(manager, source, true_child, converted);
+            // Built with arithmetic, so that this manager may order the variables differently.
+            let variable = manager.variable(source.label(node));
+            let one = manager.constant(1.0);
+            let minus_one = manager.constant(-1.0);
+            let negated = manager.apply(Operation::Times, variable, minus_one);
+            let negated = manager.apply(Operation::Plus, negated, one);
+            let true_branch = manager.apply(Operation::Times, variable, true_child);
+            let false_branch = manager.apply(Operation::Times, negated, false_child);
+            let result = manager.apply(Operation::Plus, false_branch, true_branch);
+            converted.insert(node, result);
+            result
+        }
+        let manager = DEFAULT_MANAGER.with(|it| it.clone());
+        let source = diagram.manager.borrow();
+        let mut manager_mut = manager.borrow_mut();
+        // New variables keep the order they have in `diagram`.
+        let mut variables: Vec<_> = source
+            .reachable(diagram.root)
+            .into_iter()
+            .filter(|it| !source.is_terminal(*it))
+            .map(|it| (source.level(it), source.label(it)))
+            .collect();
+        variables.sort_unstable();
+        for (_, variable) in variables {
+            manager_mut.order.push(variable);
+        }
+        let root = convert(&mut manager_mut, &source, diagram.root, &mut HashMap::new());
+        drop(manager_mut);
+        Self::from_root(manager, root)
+    }
+
+    pub fn plus(&self, other: &AlgebraicDecisionDiagram) -> Self {
+        self.apply(Operation::Plus, other)
+    }
+
+    pub fn times(&self, other: &AlgebraicDecisionDiagram) -> Self {
+        self.apply(Operation::Times, other)
+    }
+
+    pub fn max(&self, other: &AlgebraicDecisionDiagram) -> Self {
+        self.apply(Operation::Max, other)
+    }
+
+    pub fn min(&self, other: &AlgebraicDecisionDiagram) -> Self {
+        self.apply(Operation::Min, other)
+    }
+
+    // Holds where the value is at least `threshold`.
+    pub fn threshold(&self, threshold: f64) -> BinaryDecisionDiagram {
+        fn convert(
+            manager: &mut Manager,
+            source: &AlgebraicManager,
+            node: NodeIndex,
+            threshold: f64,
+            converted: &mut HashMap<NodeIndex, NodeIndex>,
+        ) -> NodeIndex {
+            if let Some(value) = source.store.terminal_value(node) {
+                return manager.constant(value >= threshold);
+            }
+            if let Some(result) = converted.get(&node) {
+                return *result;
+            }
+            let (false_child, true_child) = source.store.children(node);
+            let false_child = convert(manager, source, false_child, threshold, converted);
+            let true_child = convert(manager, source, true_child, threshold, converted);
+            let variable = manager.variable(&source.label(node));
+            let result = manager.ite(variable, true_child, false_child);
+            converted.insert(node, result);
+            result
+        }
+        let manager = Manager::shared();
+        let root = convert(
+            &mut manager.borrow_mut(),
+            &self.manager.borrow(),
+            self.root,
+            threshold,
+            &mut HashMap::new(),
+        );
+        BinaryDecisionDiagram::from_root(manager, root)
+    }
+
+    // Sums the values of both assignments of each variable in `variables`,
+    // a variable the diagram does not depend on doubles it.
+    pub fn sum_abstract(&self, variables: Vec<String>) -> Self {
+        let mut manager = self.manager.borrow_mut();
+        let root = variables.iter().fold(self.root, |root, variable| {
+            let false_child = manager.restrict(root, variable, false);
+            let true_child = manager.restrict(root, variable, true);
+            manager.apply(Operation::Plus, false_child, true_child)
+        });
+        drop(manager);
+        self.with_root(root)
+    }
+
+    // `assignment` is a plain object mapping variable names to booleans.
+    #[wasm_bindgen(js_name = eval)]
+    pub fn eval_js(&self, assignment: &JsValue) -> Result<f64, JsValue> {
+        self.eval_with(|variable| {
+            js_sys::Reflect::get(assignment, &JsValue::from_str(variable))?
+                .as_bool()
+                .ok_or_else(|| {
+                    JsValue::from_str(&format!("variable `{variable}` is not assigned a boolean"))
+                })
+        })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.manager
+            .borrow()
+            .store
+            .reachable_from(&[self.root])
+            .len()
+    }
+
+    pub fn equivalent(&self, other: &AlgebraicDecisionDiagram) -> bool {
+        self.root == other.root
+    }
+
+    pub fn dot(&self) -> String {
+        let manager = self.manager.borrow();
+        let graph = manager
+            .store
+            .subgraph(self.root, |node| manager.label(node));
+        Dot::new(&graph).to_string()
+    }
+}
+
+impl AlgebraicDecisionDiagram {
+    // Returns the name of the first unassigned variable the result depends on, if any.
+    pub fn try_eval(&self, ctx: &HashMap<String, bool>) -> Result<f64, String> {
+        self.eval_with(|variable| {
+            ctx.get(variable)
+                .copied()
+                .ok_or_else(|| variable.to_string())
+        })
+    }
+
+    fn eval_with<E>(&self, mut value_of: impl FnMut(&str) -> Result<bool, E>) -> Result<f64, E> {
+        let manager = self.manager.borrow();
+        let mut node = self.root;
+        loop {
+            if let Some(value) = manager.store.terminal_value(node) {
+                return Ok(value);
+            }
+            let (false_child, true_child) = manager.store.children(node);
+            node = if value_of(&manager.label(node))? {
+                true_child
+            } else {
+                false_child
+            };
+        }
+    }
+
+    fn apply(&self, operation: Operation, other: &Self) -> Self {
+        let root = self
+            .manager
+            .borrow_mut()
+            .apply(operation, self.root, other.root);
+        self.with_root(root)
+    }
+
+    fn from_root(manager: Rc<RefCell<AlgebraicManager>>, root: NodeIndex) -> Self {
+        {
+            let mut manager = manager.borrow_mut();
+            manager.store.reference(root);
+            manager.collect_garbage_if_needed();
+        }
+        Self { manager, root }
+    }
+
+    fn with_root(&self, root: NodeIndex) -> Self {
+        Self::from_root(self.manager.clone(), root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(a: bool, b: bool) -> HashMap<String, bool> {
+        [("a".to_string(), a), ("b".to_string(), b)]
+            .into_iter()
+            .collect()
+    }
+
+    // 3 for `a` and 2 for `b`.
+    fn cost() -> AlgebraicDecisionDiagram {
+        let a = AlgebraicDecisionDiagram::variable("a");
+        let b = AlgebraicDecisionDiagram::variable("b");
+        a.times(&AlgebraicDecisionDiagram::constant(3.0))
+            .plus(&b.times(&AlgebraicDecisionDiagram::constant(2.0)))
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let cost = cost();
+        for (a, b, expected) in [
+            (false, false, 0.0),
+            (false, true, 2.0),
+            (true, false, 3.0),
+            (true, true, 5.0),
+        ] {
+            assert_eq!(cost.try_eval(&assignment(a, b)), Ok(expected));
+        }
+        let ceiling = cost.min(&AlgebraicDecisionDiagram::constant(2.5));
+        assert_eq!(ceiling.try_eval(&assignment(true, true)), Ok(2.5));
+        assert_eq!(ceiling.try_eval(&assignment(false, true)), Ok(2.0));
+        let floor = cost.max(&AlgebraicDecisionDiagram::constant(1.0));
+        assert_eq!(floor.try_eval(&assignment(false, false)), Ok(1.0));
+        let zero = cost.plus(&cost.times(&AlgebraicDecisionDiagram::constant(-1.0)));
+        assert!(zero.equivalent(&AlgebraicDecisionDiagram::constant(0.0)));
+        assert_eq!(zero.node_count(), 1);
+        assert_eq!(
+            AlgebraicDecisionDiagram::variable("a").try_eval(&HashMap::new()),
+            Err("a".to_string())
+        );
+    }
+
+    #[test]
+    fn test_threshold() {
+        let cost = cost();
+        assert!(cost
+            .threshold(3.0)
+            .equivalent(&BinaryDecisionDiagram::from_str("a")));
+        assert!(cost
+            .threshold(4.0)
+            .equivalent(&BinaryDecisionDiagram::from_str("a&b")));
+        assert!(cost.threshold(0.0).is_tautology());
+        assert!(cost.threshold(6.0).is_contradiction());
+    }
+
+    #[test]
+    fn test_binary_decision_diagram() {
+        let bdd = BinaryDecisionDiagram::from_str("a&!b|c");
+        let add = AlgebraicDecisionDiagram::from_binary_decision_diagram(&bdd);
+        assert_eq!(add.node_count(), bdd.node_count());
+        assert!(add.threshold(1.0).equivalent(&bdd));
+        let order = ["c", "b", "a"].into_iter().collect();
+        let reordered = BinaryDecisionDiagram::from_str_with_order("a&!b|c", &order);
+        let converted = AlgebraicDecisionDiagram::from_binary_decision_diagram(&reordered);
+        assert!(converted.equivalent(&add));
+    }
+
+    #[test]
+    fn test_sum_abstract() {
+        let cost = cost();
+        let summed = cost.sum_abstract(vec!["a".to_string()]);
+        assert_eq!(summed.try_eval(&assignment(false, false)), Ok(3.0));
+        assert_eq!(summed.try_eval(&assignment(false, true)), Ok(7.0));
+        let total = cost.sum_abstract(vec!["a".to_string(), "b".to_string()]);
+        assert!(total.equivalent(&AlgebraicDecisionDiagram::constant(10.0)));
+        let doubled = cost.sum_abstract(vec!["x".to_string()]);
+        assert!(doubled.equivalent(&cost.times(&AlgebraicDecisionDiagram::constant(2.0))));
+    }
+
+    #[test]
+    fn test_garbage_collection() {
+        let cost = cost();
+        // Every assignment has its own value, so each level doubles the node count.
+        let weighted = (0..13)
+            .map(|it| {
+                AlgebraicDecisionDiagram::variable(&format!("x{it}"))
+                    .times(&AlgebraicDecisionDiagram::constant(f64::from(1 << it)))
+            })
+            .reduce(|lhs, rhs| lhs.plus(&rhs))
+            .unwrap();
+        assert_eq!(weighted.node_count(), (1 << 14) - 1);
+        drop(weighted);
+        assert!(cost.manager.borrow_mut().collect_garbage() > 1 << 13);
+        assert_eq!(cost.manager.borrow().store.node_count(), cost.node_count());
+        assert_eq!(cost.try_eval(&assignment(true, true)), Ok(5.0));
+        let zero = cost.plus(&cost.times(&AlgebraicDecisionDiagram::constant(-1.0)));
+        assert!(zero.equivalent(&AlgebraicDecisionDiagram::constant(0.0)));
+    }
+
+    #[test]
+    fn test_dot() {
+        let add =
+            AlgebraicDecisionDiagram::variable("a").times(&AlgebraicDecisionDiagram::constant(0.5));
+        assert_eq!(
+            add.dot(),
+            r#"digraph {
+    0 [ label = "a" ]
+    1 [ label = "0" ]
+    2 [ label = "0.5" ]
+    0 -> 1 [ label = "false" ]
+    0 -> 2 [ label = "true" ]
+}
+"#
+        );
+    }
+}
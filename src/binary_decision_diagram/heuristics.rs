@@ -0,0 +1,246 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+};
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use super::{BinaryDecisionDiagram, Manager, VariableOrder};
+use crate::formula::Expression;
+use crate::{ContainVariable, SyntaxTree};
+
+// FORCE stops after this many iterations even if the span still shrinks.
+const FORCE_MAX_ITERATIONS: usize = 32;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderingHeuristic {
+    Alphabetical,
+    DepthFirst,
+    Force,
+    FanIn,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeuristicResult {
+    pub heuristic: OrderingHeuristic,
+    pub node_count: usize,
+    order: VariableOrder,
+}
+
+#[wasm_bindgen]
+impl HeuristicResult {
+    pub fn order(&self) -> VariableOrder {
+        self.order.clone()
+    }
+}
+
+fn depth_first_variables<'a>(node: &'a dyn SyntaxTree, result: &mut Vec<&'a str>) {
+    if let Some(name) = node.variable_name() {
+        if !result.contains(&name) {
+            result.push(name);
+        }
+    }
+    for operand in node.operands() {
+        depth_first_variables(operand, result);
+    }
+}
+
+// Variables below each operator, which FORCE treats as hyperedges.
+fn operator_supports<'a>(
+    node: &'a dyn SyntaxTree,
+    result: &mut BTreeSet<BTreeSet<&'a str>>,
+) -> BTreeSet<&'a str> {
+    if let Some(name) = node.variable_name() {
+        return [name].into_iter().collect();
+    }
+    let support: BTreeSet<_> = node
+        .operands()
+        .into_iter()
+        .flat_map(|operand| operator_supports(operand, result))
+        .collect();
+    if support.len() > 1 {
+        result.insert(support.clone());
+    }
+    support
+}
+
+// Every operator splits the weight it receives evenly among its operands.
+fn fan_in_weights<'a>(node: &'a dyn SyntaxTree, weight: f64, result: &mut HashMap<&'a str, f64>) {
+    if let Some(name) = node.variable_name() {
+        *result.entry(name).or_default() += weight;
+    }
+    let operands = node.operands();
+    let share = weight / operands.len() as f64;
+    for operand in operands {
+        fan_in_weights(operand, share, result);
+    }
+}
+
+impl VariableOrder {
+    // Variables in the order they first appear in the formula.
+    pub fn depth_first(formula: &Expression) -> Self {
+        let mut result = Vec::new();
+        depth_first_variables(formula, &mut result);
+        result.into_iter().collect()
+    }
+
+    // The FORCE heuristic by Aloul, Markov and Sakallah: variables are repeatedly moved towards
+    // the centers of gravity of the operators they appear in, starting from `depth_first`.
+    pub fn force(formula: &Expression) -> Self {
+        let mut hyperedges = BTreeSet::new();
+        operator_supports(formula, &mut hyperedges);
+        let mut order = Self::depth_first(formula);
+        let span = |order: &Self| -> usize {
+            hyperedges
+                .iter()
+                .map(|edge| {
+                    let levels = edge.iter().map(|it| order.level(it).unwrap());
+                    levels.clone().max().unwrap() - levels.min().unwrap()
+                })
+                .sum()
+        };
+        let mut best_span = span(&order);
+        for _ in 0..FORCE_MAX_ITERATIONS {
+            let centers: Vec<f64> = hyperedges
+                .iter()
+                .map(|edge| {
+                    edge.iter()
+                        .map(|it| order.level(it).unwrap() as f64)
+                        .sum::<f64>()
+                        / edge.len() as f64
+                })
+                .collect();
+            let mut positions: Vec<(f64, usize, &String)> = order
+                .iter()
+                .enumerate()
+                .map(|(level, variable)| {
+                    let forces: Vec<f64> = hyperedges
+                        .iter()
+                        .zip(&centers)
+                        .filter(|(edge, _)| edge.contains(variable.as_str()))
+                        .map(|(_, center)| *center)
+                        .collect();
+                    if forces.is_empty() {
+                        (level as f64, level, variable)
+                    } else {
+                        (
+                            forces.iter().sum::<f64>() / forces.len() as f64,
+                            level,
+                            variable,
+                        )
+                    }
+                })
+                .collect();
+            positions.sort_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0).then(lhs.1.cmp(&rhs.1)));
+            let next: Self = positions.into_iter().map(|(_, _, it)| it).collect();
+            let next_span = span(&next);
+            if next_span >= best_span {
+                break;
+            }
+            best_span = next_span;
+            order = next;
+        }
+        order
+    }
+
+    // Variables with a larger share of the formula's fan-in come first,
+    // ties keep the depth-first order.
+    pub fn fan_in(formula: &Expression) -> Self {
+        let mut weights = HashMap::new();
+        fan_in_weights(formula, 1.0, &mut weights);
+        let mut variables: Vec<_> = Self::depth_first(formula).variables();
+        variables.sort_by(|lhs, rhs| weights[rhs.as_str()].total_cmp(&weights[lhs.as_str()]));
+        variables.into_iter().collect()
+    }
+
+    pub fn from_heuristic(formula: &Expression, heuristic: OrderingHeuristic) -> Self {
+        match heuristic {
+            OrderingHeuristic::Alphabetical => formula.variables().into_iter().collect(),
+            OrderingHeuristic::DepthFirst => Self::depth_first(formula),
+            OrderingHeuristic::Force => Self::force(formula),
+            OrderingHeuristic::FanIn => Self::fan_in(formula),
+        }
+    }
+}
+
+// Builds the diagram of `formula` under every heuristic, smallest first.
+// The diagrams are built apart from the shared manager, so its order is left alone.
+pub fn compare_heuristics(formula: &Expression) -> Vec<HeuristicResult> {
+    let manager = Rc::new(RefCell::new(Manager::new()));
+    let mut result: Vec<_> = [
+        OrderingHeuristic::Alphabetical,
+        OrderingHeuristic::DepthFirst,
+        OrderingHeuristic::Force,
+        OrderingHeuristic::FanIn,
+    ]
+    .into_iter()
+    .map(|heuristic| {
+        let order = VariableOrder::from_heuristic(formula, heuristic);
+        manager.borrow_mut().set_order(&order);
+        let node_count = BinaryDecisionDiagram::from_formula_in(&manager, formula).node_count();
+        HeuristicResult {
+            heuristic,
+            node_count,
+            order,
+        }
+    })
+    .collect();
+    result.sort_by_key(|it| it.node_count);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formula::parse;
+
+    #[test]
+    fn test_depth_first() {
+        let formula = parse("c&(b|!a)|b&d").unwrap().1;
+        assert_eq!(
+            VariableOrder::depth_first(&formula).variables(),
+            vec!["c", "b", "a", "d"]
+        );
+    }
+
+    #[test]
+    fn test_force() {
+        let formula = parse("(a&d)|(b&e)|(c&f)").unwrap().1;
+        let order = VariableOrder::force(&formula);
+        for (x, y) in [("a", "d"), ("b", "e"), ("c", "f")] {
+            let distance = order.level(x).unwrap().abs_diff(order.level(y).unwrap());
+            assert_eq!(distance, 1);
+        }
+        let formula = parse("a&x|b&y").unwrap().1;
+        assert_eq!(VariableOrder::force(&formula).len(), 4);
+    }
+
+    #[test]
+    fn test_fan_in() {
+        let formula = parse("(b&c&d)|a").unwrap().1;
+        assert_eq!(
+            VariableOrder::fan_in(&formula).variables(),
+            vec!["a", "d", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_compare_heuristics() {
+        let formula = parse("a&x|b&y|c&z").unwrap().1;
+        let result = compare_heuristics(&formula);
+        assert_eq!(result.len(), 4);
+        assert_eq!(result[0].node_count, 8);
+        let alphabetical = result
+            .iter()
+            .find(|it| it.heuristic == OrderingHeuristic::Alphabetical)
+            .unwrap();
+        assert_eq!(alphabetical.node_count, 16);
+        assert_eq!(
+            alphabetical.order.variables(),
+            vec!["a", "b", "c", "x", "y", "z"]
+        );
+    }
+}
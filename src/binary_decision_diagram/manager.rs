@@ -0,0 +1,987 @@
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+};
+
+use petgraph::stable_graph::{NodeIndex, StableDiGraph};
+
+use super::node_store::{Node, NodeStore};
+use super::order::VariableOrder;
+use super::reorder::ReorderReport;
+
+thread_local! {
+    static DEFAULT_MANAGER: RefCell<Rc<RefCell<Manager>>> =
+        RefCell::new(Rc::new(RefCell::new(Manager::new())));
+}
+
+// Truth table of a binary boolean operator, bit `(lhs << 1) | rhs` holds `f(lhs, rhs)`.
+pub(crate) fn truth_table(f: fn(bool, bool) -> bool) -> u8 {
+    let mut result = 0;
+    for lhs in [false, true] {
+        for rhs in [false, true] {
+            if f(lhs, rhs) {
+                result |= 1 << ((lhs as u8) << 1 | rhs as u8);
+            }
+        }
+    }
+    result
+}
+
+fn eval_truth_table(truth_table: u8, lhs: bool, rhs: bool) -> bool {
+    truth_table & (1 << ((lhs as u8) << 1 | rhs as u8)) != 0
+}
+
+// Index of a variable in the manager's name table, which does not change when reordering.
+pub type VariableId = usize;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum CacheKey {
+    Apply(u8, NodeIndex, NodeIndex),
+    Ite(NodeIndex, NodeIndex, NodeIndex),
+    Restrict(NodeIndex, VariableId, bool),
+    Constrain(NodeIndex, NodeIndex),
+    RestrictBy(NodeIndex, NodeIndex),
+}
+
+// Owns the nodes of every diagram built in it.
+// The unique table guarantees that each boolean function is represented by exactly one node,
+// so two diagrams in the same manager are equivalent iff their roots are the same node.
+pub struct Manager {
+    store: NodeStore<bool>,
+    order: VariableOrder,
+    names: Vec<String>,
+    ids: HashMap<String, VariableId>,
+    // Level of each variable id, and the variable id at each level, kept in sync with `order`.
+    levels: Vec<usize>,
+    level_variables: Vec<VariableId>,
+    computed_cache: HashMap<CacheKey, NodeIndex>,
+    false_node: NodeIndex,
+    true_node: NodeIndex,
+    pub(super) auto_reorder_threshold: Option<usize>,
+    pub(super) last_reorder: Option<ReorderReport>,
+}
+
+impl Default for Manager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Manager {
+    pub fn new() -> Self {
+        Self::with_order(VariableOrder::default())
+    }
+
+    pub fn with_order(order: VariableOrder) -> Self {
+        let mut store = NodeStore::new();
+        // Both terminals stay alive, even when no diagram refers to them.
+        let false_node = store.terminal(false);
+        let true_node = store.terminal(true);
+        store.reference(false_node);
+        store.reference(true_node);
+        let mut result = Self {
+            store,
+            order: VariableOrder::default(),
+            names: Vec::new(),
+            ids: HashMap::new(),
+            levels: Vec::new(),
+            level_variables: Vec::new(),
+            computed_cache: HashMap::new(),
+            false_node,
+            true_node,
+            auto_reorder_threshold: None,
+            last_reorder: None,
+        };
+        result.add_variables(order.iter());
+        result
+    }
+
+    pub fn shared() -> Rc<RefCell<Self>> {
+        DEFAULT_MANAGER.with(|it| it.borrow().clone())
+    }
+
+    // Existing diagrams in the shared manager are reordered in place.
+    pub fn set_shared_order(order: VariableOrder) {
+        Self::shared().borrow_mut().set_order(&order);
+    }
+
+    pub fn order(&self) -> &VariableOrder {
+        &self.order
+    }
+
+    // Variables unknown to the order are placed below all known ones.
+    pub fn add_variables<S: AsRef<str>>(&mut self, variables: impl IntoIterator<Item = S>) {
+        for variable in variables {
+            self.variable_id(variable.as_ref());
+        }
+    }
+
+    // The id of `name`, which is placed below all known variables if it is new.
+    pub fn variable_id(&mut self, name: &str) -> VariableId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = self.names.len();
+        let level = self.order.push(name);
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.levels.push(level);
+        self.level_variables.push(id);
+        id
+    }
+
+    pub fn variable_name(&self, id: VariableId) -> &str {
+        &self.names[id]
+    }
+
+    pub fn variable_level(&self, id: VariableId) -> usize {
+        self.levels[id]
+    }
+
+    pub fn variable_at(&self, level: usize) -> VariableId {
+        self.level_variables[level]
+    }
+
+    pub fn constant(&self, value: bool) -> NodeIndex {
+        if value {
+            self.true_node
+        } else {
+            self.false_node
+        }
+    }
+
+    pub fn variable(&mut self, name: &str) -> NodeIndex {
+        let id = self.variable_id(name);
+        self.make_node(id, self.false_node, self.true_node)
+    }
+
+    pub fn node(&self, node: NodeIndex) -> Node {
+        self.store.node(node)
+    }
+
+    pub fn terminal_value(&self, node: NodeIndex) -> Option<bool> {
+        self.store.terminal_value(node)
+    }
+
+    pub fn is_terminal(&self, node: NodeIndex) -> bool {
+        self.store.is_terminal(node)
+    }
+
+    pub fn label(&self, node: NodeIndex) -> &str {
+        match self.store.node(node) {
+            Node::Terminal(true) => "true",
+            Node::Terminal(false) => "false",
+            Node::Variable(id) => &self.names[id],
+        }
+    }
+
+    // The variable a non-terminal node tests.
+    pub fn node_variable(&self, node: NodeIndex) -> VariableId {
+        self.store.node_variable(node)
+    }
+
+    // Returns `(false_child, true_child)` of a non-terminal node.
+    pub fn children(&self, node: NodeIndex) -> (NodeIndex, NodeIndex) {
+        self.store.children(node)
+    }
+
+    // Terminals are placed below every variable.
+    pub fn level(&self, node: NodeIndex) -> usize {
+        match self.store.node(node) {
+            Node::Terminal(_) => usize::MAX,
+            Node::Variable(id) => self.levels[id],
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.store.node_count()
+    }
+
+    pub fn reference(&mut self, node: NodeIndex) {
+        self.store.reference(node);
+    }
+
+    pub fn dereference(&mut self, node: NodeIndex) {
+        self.store.dereference(node);
+    }
+
+    // Number of nodes reachable from referenced roots, including both terminals.
+    pub fn live_node_count(&self) -> usize {
+        self.store.live_nodes().len()
+    }
+
+    // Removes every node unreachable from a referenced root and returns how many were removed.
+    // Node indices not held by a root must not be used afterwards.
+    pub fn collect_garbage(&mut self) -> usize {
+        let result = self.store.collect_garbage();
+        self.computed_cache.clear();
+        result
+    }
+
+    // Called between operations, when every node in use is held by a referenced root.
+    pub fn collect_garbage_if_needed(&mut self) {
+        if self.store.needs_collection() {
+            self.collect_garbage();
+        }
+    }
+
+    pub(super) fn nodes_of(&self, variable: VariableId) -> Vec<NodeIndex> {
+        self.store.nodes_of(variable)
+    }
+
+    pub(super) fn variable_node_count(&self, variable: VariableId) -> usize {
+        self.store.variable_node_count(variable)
+    }
+
+    // Removes `node`, and then its children, if nothing refers to it any more.
+    pub(super) fn remove_if_dead(&mut self, node: NodeIndex) {
+        self.store.remove_if_dead(node);
+    }
+
+    // Rewrites `node` in place, so that handles to it stay valid.
+    // The caller has to make sure the node still represents the same function.
+    pub(super) fn replace_node(
+        &mut self,
+        node: NodeIndex,
+        variable: VariableId,
+        false_child: NodeIndex,
+        true_child: NodeIndex,
+    ) {
+        self.store
+            .replace_node(node, variable, false_child, true_child);
+    }
+
+    // Exchanges the variables at `level` and `level + 1` in the order only.
+    pub(super) fn swap_order(&mut self, level: usize) {
+        self.order.swap(level);
+        self.level_variables.swap(level, level + 1);
+        self.levels[self.level_variables[level]] = level;
+        self.levels[self.level_variables[level + 1]] = level + 1;
+    }
+
+    pub(super) fn clear_computed_cache(&mut self) {
+        self.computed_cache.clear();
+    }
+
+    pub fn make_node(
+        &mut self,
+        variable: VariableId,
+        false_child: NodeIndex,
+        true_child: NodeIndex,
+    ) -> NodeIndex {
+        if false_child == true_child {
+            return false_child;
+        }
+        let level = self.levels[variable];
+        debug_assert!(level < self.level(false_child) && level < self.level(true_child));
+        self.store.make_node(variable, false_child, true_child)
+    }
+
+    pub fn apply(
+        &mut self,
+        f: fn(bool, bool) -> bool,
+        lhs: NodeIndex,
+        rhs: NodeIndex,
+    ) -> NodeIndex {
+        self.apply_truth_table(truth_table(f), lhs, rhs)
+    }
+
+    pub(crate) fn apply_truth_table(
+        &mut self,
+        truth_table: u8,
+        lhs: NodeIndex,
+        rhs: NodeIndex,
+    ) -> NodeIndex {
+        if let (Some(lhs_value), Some(rhs_value)) =
+            (self.terminal_value(lhs), self.terminal_value(rhs))
+        {
+            return self.constant(eval_truth_table(truth_table, lhs_value, rhs_value));
+        }
+        let key = CacheKey::Apply(truth_table, lhs, rhs);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let (variable, (lhs_false, lhs_true), (rhs_false, rhs_true)) =
+            match self.level(lhs).cmp(&self.level(rhs)) {
+                Ordering::Less => (self.node_variable(lhs), self.children(lhs), (rhs, rhs)),
+                Ordering::Greater => (self.node_variable(rhs), (lhs, lhs), self.children(rhs)),
+                Ordering::Equal => (
+                    self.node_variable(lhs),
+                    self.children(lhs),
+                    self.children(rhs),
+                ),
+            };
+        let false_child = self.apply_truth_table(truth_table, lhs_false, rhs_false);
+        let true_child = self.apply_truth_table(truth_table, lhs_true, rhs_true);
+        let result = self.make_node(variable, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    // Children of `node` if it is labelled with the variable at `level`,
+    // otherwise the function does not depend on that variable.
+    fn cofactors_at(&self, node: NodeIndex, level: usize) -> (NodeIndex, NodeIndex) {
+        if self.level(node) == level {
+            self.children(node)
+        } else {
+            (node, node)
+        }
+    }
+
+    // `condition ? then : otherwise`, splitting all three on their topmost variable at once.
+    pub fn ite(
+        &mut self,
+        condition: NodeIndex,
+        then: NodeIndex,
+        otherwise: NodeIndex,
+    ) -> NodeIndex {
+        match self.terminal_value(condition) {
+            Some(true) => return then,
+            Some(false) => return otherwise,
+            None => {}
+        }
+        if then == otherwise {
+            return then;
+        }
+        if then == self.true_node && otherwise == self.false_node {
+            return condition;
+        }
+        let key = CacheKey::Ite(condition, then, otherwise);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let level = [condition, then, otherwise]
+            .into_iter()
+            .map(|it| self.level(it))
+            .min()
+            .unwrap();
+        let variable = self.variable_at(level);
+        let (condition_false, condition_true) = self.cofactors_at(condition, level);
+        let (then_false, then_true) = self.cofactors_at(then, level);
+        let (otherwise_false, otherwise_true) = self.cofactors_at(otherwise, level);
+        let false_child = self.ite(condition_false, then_false, otherwise_false);
+        let true_child = self.ite(condition_true, then_true, otherwise_true);
+        let result = self.make_node(variable, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    pub fn not(&mut self, node: NodeIndex) -> NodeIndex {
+        self.apply(|lhs, _| !lhs, node, self.true_node)
+    }
+
+    pub fn restrict(&mut self, node: NodeIndex, variable: &str, value: bool) -> NodeIndex {
+        if self.is_terminal(node) {
+            return node;
+        }
+        let Some(id) = self.ids.get(variable).copied() else {
+            return node;
+        };
+        let key = CacheKey::Restrict(node, id, value);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let level = self.levels[id];
+        let node_variable = self.node_variable(node);
+        let (false_child, true_child) = self.children(node);
+        let result = match self.level(node).cmp(&level) {
+            Ordering::Greater => node,
+            Ordering::Equal if value => true_child,
+            Ordering::Equal => false_child,
+            Ordering::Less => {
+                let false_child = self.restrict(false_child, variable, value);
+                let true_child = self.restrict(true_child, variable, value);
+                self.make_node(node_variable, false_child, true_child)
+            }
+        };
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    // Restricts every variable in `cube` to its value in one pass.
+    pub fn cofactor(&mut self, node: NodeIndex, cube: &HashMap<String, bool>) -> NodeIndex {
+        fn recursive_cofactor(
+            manager: &mut Manager,
+            node: NodeIndex,
+            cube: &HashMap<usize, bool>,
+            last: usize,
+            cofactored: &mut HashMap<NodeIndex, NodeIndex>,
+        ) -> NodeIndex {
+            let level = manager.level(node);
+            if level > last {
+                return node;
+            }
+            if let Some(result) = cofactored.get(&node) {
+                return *result;
+            }
+            let variable = manager.node_variable(node);
+            let (false_child, true_child) = manager.children(node);
+            let result = match cube.get(&level) {
+                Some(true) => recursive_cofactor(manager, true_child, cube, last, cofactored),
+                Some(false) => recursive_cofactor(manager, false_child, cube, last, cofactored),
+                None => {
+                    let false_child =
+                        recursive_cofactor(manager, false_child, cube, last, cofactored);
+                    let true_child =
+                        recursive_cofactor(manager, true_child, cube, last, cofactored);
+                    manager.make_node(variable, false_child, true_child)
+                }
+            };
+            cofactored.insert(node, result);
+            result
+        }
+        let cube: HashMap<_, _> = cube
+            .iter()
+            .filter_map(|(variable, value)| Some((self.order.level(variable)?, *value)))
+            .collect();
+        let Some(last) = cube.keys().max().copied() else {
+            return node;
+        };
+        recursive_cofactor(self, node, &cube, last, &mut HashMap::new())
+    }
+
+    // Coudert and Madre's generalized cofactor: agrees with `node` wherever `care` holds,
+    // and maps every other point to the value at the nearest point of `care`.
+    pub fn constrain(&mut self, node: NodeIndex, care: NodeIndex) -> NodeIndex {
+        if care == self.false_node {
+            return self.false_node;
+        }
+        if care == self.true_node || self.is_terminal(node) {
+            return node;
+        }
+        if node == care {
+            return self.true_node;
+        }
+        let key = CacheKey::Constrain(node, care);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let level = self.level(node).min(self.level(care));
+        let variable = self.variable_at(level);
+        let (node_false, node_true) = self.cofactors_at(node, level);
+        let (care_false, care_true) = self.cofactors_at(care, level);
+        let result = if care_false == self.false_node {
+            self.constrain(node_true, care_true)
+        } else if care_true == self.false_node {
+            self.constrain(node_false, care_false)
+        } else {
+            let false_child = self.constrain(node_false, care_false);
+            let true_child = self.constrain(node_true, care_true);
+            self.make_node(variable, false_child, true_child)
+        };
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    // Coudert and Madre's restrict: like `constrain`, but variables of `care` that `node` does not
+    // test are quantified away first, so the result never depends on new variables.
+    pub fn restrict_by(&mut self, node: NodeIndex, care: NodeIndex) -> NodeIndex {
+        if care == self.false_node {
+            return self.false_node;
+        }
+        if care == self.true_node || self.is_terminal(node) {
+            return node;
+        }
+        if node == care {
+            return self.true_node;
+        }
+        let key = CacheKey::RestrictBy(node, care);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let level = self.level(node);
+        let result = if self.level(care) < level {
+            let (care_false, care_true) = self.children(care);
+            let care = self.apply(|a, b| a || b, care_false, care_true);
+            self.restrict_by(node, care)
+        } else {
+            let variable = self.node_variable(node);
+            let (node_false, node_true) = self.children(node);
+            let (care_false, care_true) = self.cofactors_at(care, level);
+            if care_false == self.false_node {
+                self.restrict_by(node_true, care_true)
+            } else if care_true == self.false_node {
+                self.restrict_by(node_false, care_false)
+            } else {
+                let false_child = self.restrict_by(node_false, care_false);
+                let true_child = self.restrict_by(node_true, care_true);
+                self.make_node(variable, false_child, true_child)
+            }
+        };
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    pub fn exists(&mut self, node: NodeIndex, variable: &str) -> NodeIndex {
+        self.quantify(node, &[variable], truth_table(|a, b| a || b))
+    }
+
+    pub fn universal(&mut self, node: NodeIndex, variable: &str) -> NodeIndex {
+        self.quantify(node, &[variable], truth_table(|a, b| a && b))
+    }
+
+    pub fn exists_many<S: AsRef<str>>(&mut self, node: NodeIndex, variables: &[S]) -> NodeIndex {
+        self.quantify(node, variables, truth_table(|a, b| a || b))
+    }
+
+    pub fn universal_many<S: AsRef<str>>(&mut self, node: NodeIndex, variables: &[S]) -> NodeIndex {
+        self.quantify(node, variables, truth_table(|a, b| a && b))
+    }
+
+    // `f|variable=0 ^ f|variable=1`, true where flipping `variable` changes the result.
+    pub fn boolean_difference(&mut self, node: NodeIndex, variable: &str) -> NodeIndex {
+        self.derivative(node, &[variable])
+    }
+
+    // Repeated boolean difference over all `variables`,
+    // the xor of the function over every assignment to them.
+    pub fn derivative<S: AsRef<str>>(&mut self, node: NodeIndex, variables: &[S]) -> NodeIndex {
+        self.quantify(node, variables, truth_table(|a, b| a ^ b))
+    }
+
+    // True where exactly one assignment to `variables` satisfies the function.
+    pub fn unique_exists<S: AsRef<str>>(&mut self, node: NodeIndex, variables: &[S]) -> NodeIndex {
+        // Returns the unique and the plain existential quantification of the variables at or
+        // below the level of `node`.
+        fn recursive_unique_exists(
+            manager: &mut Manager,
+            node: NodeIndex,
+            levels: &BTreeSet<usize>,
+            quantified: &mut HashMap<NodeIndex, (NodeIndex, NodeIndex)>,
+        ) -> (NodeIndex, NodeIndex) {
+            let level = manager.level(node);
+            if levels.last().is_none_or(|last| level > *last) {
+                return (node, node);
+            }
+            if let Some(result) = quantified.get(&node) {
+                return *result;
+            }
+            let variable = manager.node_variable(node);
+            let (false_child, true_child) = manager.children(node);
+            let [(unique_false, exists_false), (unique_true, exists_true)] =
+                [false_child, true_child].map(|child| {
+                    let (unique, exists) =
+                        recursive_unique_exists(manager, child, levels, quantified);
+                    // Both values of a skipped variable satisfy the function alike.
+                    if levels
+                        .range(level + 1..manager.level(child))
+                        .next()
+                        .is_some()
+                    {
+                        (manager.false_node, exists)
+                    } else {
+                        (unique, exists)
+                    }
+                });
+            let result = if levels.contains(&level) {
+                // Unique for one value of the variable and unsatisfiable for the other.
+                let only_false = manager.apply(
+                    |unique, exists| unique && !exists,
+                    unique_false,
+                    exists_true,
+                );
+                let only_true = manager.apply(
+                    |unique, exists| unique && !exists,
+                    unique_true,
+                    exists_false,
+                );
+                (
+                    manager.apply(|a, b| a || b, only_false, only_true),
+                    manager.apply(|a, b| a || b, exists_false, exists_true),
+                )
+            } else {
+                (
+                    manager.make_node(variable, unique_false, unique_true),
+                    manager.make_node(variable, exists_false, exists_true),
+                )
+            };
+            quantified.insert(node, result);
+            result
+        }
+        let levels = self.levels_of(variables);
+        let unknown = variables
+            .iter()
+            .any(|it| self.order.level(it.as_ref()).is_none());
+        if unknown || levels.range(..self.level(node)).next().is_some() {
+            return self.false_node;
+        }
+        recursive_unique_exists(self, node, &levels, &mut HashMap::new()).0
+    }
+
+    // Levels of the known `variables`, unknown ones can not occur in any diagram.
+    fn levels_of<S: AsRef<str>>(&self, variables: &[S]) -> BTreeSet<usize> {
+        variables
+            .iter()
+            .filter_map(|it| self.order.level(it.as_ref()))
+            .collect()
+    }
+
+    // Removes all `variables` in one pass, combining the two cofactors of each with `truth_table`.
+    fn quantify<S: AsRef<str>>(
+        &mut self,
+        node: NodeIndex,
+        variables: &[S],
+        truth_table: u8,
+    ) -> NodeIndex {
+        // Quantifying a variable the function does not depend on combines the function with itself,
+        // which only changes something for operators like xor.
+        fn skip(
+            manager: &mut Manager,
+            node: NodeIndex,
+            times: usize,
+            truth_table: u8,
+        ) -> NodeIndex {
+            let idempotent = !eval_truth_table(truth_table, false, false)
+                && eval_truth_table(truth_table, true, true);
+            if idempotent {
+                return node;
+            }
+            (0..times).fold(node, |node, _| {
+                manager.apply_truth_table(truth_table, node, node)
+            })
+        }
+        // Quantifies the variables at or below the level of `node`.
+        fn recursive_quantify(
+            manager: &mut Manager,
+            node: NodeIndex,
+            levels: &BTreeSet<usize>,
+            truth_table: u8,
+            quantified: &mut HashMap<NodeIndex, NodeIndex>,
+        ) -> NodeIndex {
+            let level = manager.level(node);
+            // Nothing below the last quantified variable changes.
+            if levels.last().is_none_or(|last| level > *last) {
+                return node;
+            }
+            if let Some(result) = quantified.get(&node) {
+                return *result;
+            }
+            let variable = manager.node_variable(node);
+            let (false_child, true_child) = manager.children(node);
+            let [false_child, true_child] = [false_child, true_child].map(|child| {
+                let skipped = levels.range(level + 1..manager.level(child)).count();
+                let child = recursive_quantify(manager, child, levels, truth_table, quantified);
+                skip(manager, child, skipped, truth_table)
+            });
+            let result = if levels.contains(&level) {
+                manager.apply_truth_table(truth_table, false_child, true_child)
+            } else {
+                manager.make_node(variable, false_child, true_child)
+            };
+            quantified.insert(node, result);
+            result
+        }
+        let levels = self.levels_of(variables);
+        let unknown: BTreeSet<_> = variables
+            .iter()
+            .map(|it| it.as_ref())
+            .filter(|it| self.order.level(it).is_none())
+            .collect();
+        let skipped = levels.range(..self.level(node)).count() + unknown.len();
+        let result = recursive_quantify(self, node, &levels, truth_table, &mut HashMap::new());
+        skip(self, result, skipped, truth_table)
+    }
+
+    // The relational product `exists variables. lhs & rhs`, without building `lhs & rhs` first.
+    pub fn and_exists<S: AsRef<str>>(
+        &mut self,
+        lhs: NodeIndex,
+        rhs: NodeIndex,
+        variables: &[S],
+    ) -> NodeIndex {
+        fn recursive_and_exists(
+            manager: &mut Manager,
+            lhs: NodeIndex,
+            rhs: NodeIndex,
+            levels: &BTreeSet<usize>,
+            computed: &mut HashMap<(NodeIndex, NodeIndex), NodeIndex>,
+        ) -> NodeIndex {
+            if lhs == manager.false_node || rhs == manager.false_node {
+                return manager.false_node;
+            }
+            let level = manager.level(lhs).min(manager.level(rhs));
+            if levels.last().is_none_or(|last| level > *last) {
+                return manager.apply(|a, b| a && b, lhs, rhs);
+            }
+            if let Some(result) = computed.get(&(lhs, rhs)) {
+                return *result;
+            }
+            let variable = manager.variable_at(level);
+            let (lhs_false, lhs_true) = manager.cofactors_at(lhs, level);
+            let (rhs_false, rhs_true) = manager.cofactors_at(rhs, level);
+            let false_child = recursive_and_exists(manager, lhs_false, rhs_false, levels, computed);
+            let result = if levels.contains(&level) {
+                if false_child == manager.true_node {
+                    manager.true_node
+                } else {
+                    let true_child =
+                        recursive_and_exists(manager, lhs_true, rhs_true, levels, computed);
+                    manager.apply(|a, b| a || b, false_child, true_child)
+                }
+            } else {
+                let true_child =
+                    recursive_and_exists(manager, lhs_true, rhs_true, levels, computed);
+                manager.make_node(variable, false_child, true_child)
+            };
+            computed.insert((lhs, rhs), result);
+            result
+        }
+        let levels = self.levels_of(variables);
+        recursive_and_exists(self, lhs, rhs, &levels, &mut HashMap::new())
+    }
+
+    // Replaces `variable` in the function rooted at `node` with the function rooted at `replacement`.
+    pub fn compose(
+        &mut self,
+        node: NodeIndex,
+        variable: &str,
+        replacement: NodeIndex,
+    ) -> NodeIndex {
+        self.vector_compose(node, &[(variable.to_string(), replacement)].into())
+    }
+
+    // Replaces all variables in `substitution` at once, so a replacement is never
+    // itself substituted into.
+    pub fn vector_compose(
+        &mut self,
+        node: NodeIndex,
+        substitution: &HashMap<String, NodeIndex>,
+    ) -> NodeIndex {
+        fn recursive_compose(
+            manager: &mut Manager,
+            node: NodeIndex,
+            substitution: &HashMap<String, NodeIndex>,
+            composed: &mut HashMap<NodeIndex, NodeIndex>,
+        ) -> NodeIndex {
+            if manager.is_terminal(node) {
+                return node;
+            }
+            if let Some(result) = composed.get(&node) {
+                return *result;
+            }
+            let variable = manager.node_variable(node);
+            let (false_child, true_child) = manager.children(node);
+            let false_child = recursive_compose(manager, false_child, substitution, composed);
+            let true_child = recursive_compose(manager, true_child, substitution, composed);
+            let condition = match substitution.get(manager.variable_name(variable)) {
+                Some(replacement) => *replacement,
+                None => manager.make_node(variable, manager.false_node, manager.true_node),
+            };
+            let result = manager.ite(condition, true_child, false_child);
+            composed.insert(node, result);
+            result
+        }
+        recursive_compose(self, node, substitution, &mut HashMap::new())
+    }
+
+    // Renames variables simultaneously, so permutations like swapping two variables work.
+    // The diagram is rebuilt rather than relabelled, since the new names may sit anywhere in the order.
+    pub fn rename(&mut self, node: NodeIndex, renaming: &HashMap<String, String>) -> NodeIndex {
+        let substitution = renaming
+            .iter()
+            .map(|(from, to)| (from.clone(), self.variable(to)))
+            .collect();
+        self.vector_compose(node, &substitution)
+    }
+
+    // Copies the diagram rooted at `node` in `source` into this manager, following this manager's order.
+    pub fn import(&mut self, source: &Manager, node: NodeIndex) -> NodeIndex {
+        fn recursive_import(
+            manager: &mut Manager,
+            source: &Manager,
+            node: NodeIndex,
+            imported: &mut HashMap<NodeIndex, NodeIndex>,
+        ) -> NodeIndex {
+            if let Some(value) = source.terminal_value(node) {
+                return manager.constant(value);
+            }
+            if let Some(result) = imported.get(&node) {
+                return *result;
+            }
+            let (false_child, true_child) = source.children(node);
+            let false_child = recursive_import(manager, source, false_child, imported);
+            let true_child = recursive_import(manager, source, true_child, imported);
+            let variable = manager.variable(source.label(node));
+            let false_branch = manager.apply(|x, f| !x && f, variable, false_child);
+            let true_branch = manager.apply(|x, t| x && t, variable, true_child);
+            let result = manager.apply(|f, t| f || t, false_branch, true_branch);
+            imported.insert(node, result);
+            result
+        }
+        let mut variables: Vec<_> = source
+            .reachable(node)
+            .into_iter()
+            .filter(|it| !source.is_terminal(*it))
+            .map(|it| (source.level(it), source.label(it)))
+            .collect();
+        variables.sort_unstable();
+        self.add_variables(variables.into_iter().map(|(_, variable)| variable));
+        recursive_import(self, source, node, &mut HashMap::new())
+    }
+
+    // Variables along one path from `node` to the true terminal, preferring false edges.
+    pub fn any_sat(&self, mut node: NodeIndex) -> Option<Vec<(String, bool)>> {
+        if node == self.false_node {
+            return None;
+        }
+        let mut result = Vec::new();
+        while !self.is_terminal(node) {
+            let (false_child, true_child) = self.children(node);
+            let value = false_child == self.false_node;
+            result.push((self.label(node).to_string(), value));
+            node = if value { true_child } else { false_child };
+        }
+        Some(result)
+    }
+
+    // Variables the function rooted at `node` depends on.
+    pub fn support(&self, node: NodeIndex) -> BTreeSet<String> {
+        self.reachable(node)
+            .into_iter()
+            .filter(|it| !self.is_terminal(*it))
+            .map(|it| self.label(it).to_string())
+            .collect()
+    }
+
+    pub fn depends_on(&self, node: NodeIndex, variable: &str) -> bool {
+        let Some(level) = self.order.level(variable) else {
+            return false;
+        };
+        self.reachable(node)
+            .into_iter()
+            .any(|it| self.level(it) == level)
+    }
+
+    // Nodes reachable from `root`, in depth-first order.
+    pub fn reachable(&self, root: NodeIndex) -> Vec<NodeIndex> {
+        self.reachable_from(&[root])
+    }
+
+    // Nodes reachable from any of `roots`, each listed once.
+    pub fn reachable_from(&self, roots: &[NodeIndex]) -> Vec<NodeIndex> {
+        self.store.reachable_from(roots)
+    }
+
+    // Copies the diagram rooted at `root` out of the shared node store.
+    pub fn subgraph(&self, root: NodeIndex) -> StableDiGraph<String, bool> {
+        self.store
+            .subgraph(root, |node| self.label(node).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_truth_table() {
+        assert_eq!(truth_table(|a, b| a && b), 0b1000);
+        assert_eq!(truth_table(|a, b| a || b), 0b1110);
+        assert_eq!(truth_table(|a, b| a ^ b), 0b0110);
+        assert!(eval_truth_table(0b0110, true, false));
+        assert!(!eval_truth_table(0b0110, true, true));
+    }
+
+    #[test]
+    fn test_canonicity() {
+        let mut manager = Manager::new();
+        let a = manager.variable("a");
+        let b = manager.variable("b");
+        let c = manager.variable("c");
+        let a_and_b = manager.apply(|x, y| x && y, a, b);
+        let b_and_a = manager.apply(|x, y| x && y, b, a);
+        assert_eq!(a_and_b, b_and_a);
+
+        let a_or_b = manager.apply(|x, y| x || y, a, b);
+        let lhs = manager.apply(|x, y| x && y, a_or_b, c);
+        let a_and_c = manager.apply(|x, y| x && y, a, c);
+        let b_and_c = manager.apply(|x, y| x && y, b, c);
+        let rhs = manager.apply(|x, y| x || y, a_and_c, b_and_c);
+        assert_eq!(lhs, rhs);
+
+        let not_a = manager.not(a);
+        let tautology = manager.apply(|x, y| x || y, a, not_a);
+        assert_eq!(tautology, manager.constant(true));
+        assert_eq!(manager.not(not_a), a);
+    }
+
+    #[test]
+    fn test_node_sharing() {
+        let mut manager = Manager::new();
+        let a = manager.variable("a");
+        let b = manager.variable("b");
+        let a_and_b = manager.apply(|x, y| x && y, a, b);
+        let node_count = manager.node_count();
+        let again = manager.apply(|x, y| x && y, a, b);
+        assert_eq!(a_and_b, again);
+        assert_eq!(manager.node_count(), node_count);
+        assert_eq!(manager.reachable(a_and_b).len(), 4);
+    }
+
+    #[test]
+    fn test_variable_named_like_terminal() {
+        let mut manager = Manager::new();
+        let variable = manager.variable("true");
+        assert!(!manager.is_terminal(variable));
+        assert_eq!(manager.node(variable), Node::Variable(0));
+        assert_eq!(manager.label(variable), "true");
+        let (false_child, true_child) = manager.children(variable);
+        assert_eq!(manager.node(false_child), Node::Terminal(false));
+        assert_eq!(manager.node(true_child), Node::Terminal(true));
+        assert_eq!(manager.support(variable).len(), 1);
+    }
+
+    #[test]
+    fn test_quantification() {
+        let mut manager = Manager::new();
+        let a = manager.variable("a");
+        let b = manager.variable("b");
+        let a_and_b = manager.apply(|x, y| x && y, a, b);
+        assert_eq!(manager.restrict(a_and_b, "a", true), b);
+        assert_eq!(
+            manager.restrict(a_and_b, "b", false),
+            manager.constant(false)
+        );
+        assert_eq!(manager.exists(a_and_b, "a"), b);
+        assert_eq!(manager.universal(a_and_b, "a"), manager.constant(false));
+        let c = manager.variable("c");
+        let b_or_c = manager.apply(|x, y| x || y, b, c);
+        assert_eq!(
+            manager.exists_many(a_and_b, &["a", "b"]),
+            manager.constant(true)
+        );
+        assert_eq!(manager.exists_many(b_or_c, &["a", "x"]), b_or_c);
+        assert_eq!(manager.universal_many(b_or_c, &["c"]), b);
+        assert_eq!(manager.and_exists(a_and_b, b_or_c, &["b"]), a);
+        let not_b = manager.not(b);
+        assert_eq!(
+            manager.and_exists(a_and_b, not_b, &["a"]),
+            manager.constant(false)
+        );
+    }
+
+    #[test]
+    fn test_any_sat() {
+        let mut manager = Manager::new();
+        let a = manager.variable("a");
+        let b = manager.variable("b");
+        let not_b = manager.not(b);
+        let a_and_not_b = manager.apply(|x, y| x && y, a, not_b);
+        assert_eq!(
+            manager.any_sat(a_and_not_b),
+            Some(vec![("a".to_string(), true), ("b".to_string(), false)])
+        );
+        let a_or_b = manager.apply(|x, y| x || y, a, b);
+        assert_eq!(
+            manager.any_sat(a_or_b),
+            Some(vec![("a".to_string(), false), ("b".to_string(), true)])
+        );
+        assert_eq!(manager.any_sat(manager.constant(true)), Some(vec![]));
+        assert_eq!(manager.any_sat(manager.constant(false)), None);
+        assert_eq!(
+            manager.support(a_or_b),
+            ["a".to_string(), "b".to_string()].into_iter().collect()
+        );
+    }
+}
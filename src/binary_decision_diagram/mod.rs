@@ -0,0 +1,1088 @@
+pub(crate) mod algebraic;
+pub(crate) mod heuristics;
+pub(crate) mod manager;
+pub(crate) mod node_store;
+pub(crate) mod order;
+pub(crate) mod reorder;
+pub(crate) mod serialization;
+pub(crate) mod shared;
+pub(crate) mod solutions;
+pub(crate) mod to_expression;
+pub(crate) mod zero_suppressed;
+
+pub use heuristics::{compare_heuristics, HeuristicResult, OrderingHeuristic};
+pub use manager::Manager;
+pub use order::VariableOrder;
+pub use reorder::ReorderReport;
+
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+};
+
+use petgraph::{dot::Dot, stable_graph::NodeIndex};
+use wasm_bindgen::{
+    prelude::{wasm_bindgen, JsValue},
+    JsCast,
+};
+
+use crate::formula::{expression, Expression};
+use crate::{ContainVariable, Evaluable, ToBinaryDecisionDiagram};
+
+fn assignment_to_js(assignment: &HashMap<String, bool>) -> JsValue {
+    let result = js_sys::Object::new();
+    for (variable, value) in assignment {
+        js_sys::Reflect::set(
+            &result,
+            &JsValue::from_str(variable),
+            &JsValue::from_bool(*value),
+        )
+        .unwrap();
+    }
+    result.into()
+}
+
+fn assignment_from_js(assignment: &JsValue) -> Result<HashMap<String, bool>, JsValue> {
+    let object = assignment
+        .dyn_ref::<js_sys::Object>()
+        .ok_or_else(|| JsValue::from_str("assignment must be an object"))?;
+    js_sys::Object::keys(object)
+        .iter()
+        .map(|key| {
+            let variable = key.as_string().unwrap();
+            let value = js_sys::Reflect::get(object, &key)?
+                .as_bool()
+                .ok_or_else(|| {
+                    JsValue::from_str(&format!("variable `{variable}` is not assigned a boolean"))
+                })?;
+            Ok((variable, value))
+        })
+        .collect()
+}
+
+// A lightweight handle to a diagram whose nodes live in a shared `Manager`.
+#[wasm_bindgen]
+pub struct BinaryDecisionDiagram {
+    manager: Rc<RefCell<Manager>>,
+    root: NodeIndex,
+    variables_cache: OnceCell<BTreeSet<String>>,
+}
+
+impl Clone for BinaryDecisionDiagram {
+    fn clone(&self) -> Self {
+        self.manager.borrow_mut().reference(self.root);
+        Self {
+            manager: self.manager.clone(),
+            root: self.root,
+            variables_cache: self.variables_cache.clone(),
+        }
+    }
+}
+
+impl Drop for BinaryDecisionDiagram {
+    fn drop(&mut self) {
+        if let Ok(mut manager) = self.manager.try_borrow_mut() {
+            manager.dereference(self.root);
+        }
+    }
+}
+
+impl ContainVariable for BinaryDecisionDiagram {
+    fn variables(&self) -> BTreeSet<String> {
+        self.variables_cache
+            .get_or_init(|| self.manager.borrow().support(self.root))
+            .clone()
+    }
+}
+
+// Panics like the formulas do on an unassigned variable, `try_eval` reports it instead.
+impl Evaluable for BinaryDecisionDiagram {
+    fn eval(&self, ctx: &HashMap<String, bool>) -> bool {
+        self.try_eval(ctx)
+            .unwrap_or_else(|variable| panic!("variable `{variable}` is not assigned"))
+    }
+}
+
+#[wasm_bindgen]
+impl BinaryDecisionDiagram {
+    // Diagrams are kept reduced by the manager's unique table, so there is nothing left to do here.
+    pub fn reduce(self) -> Self {
+        self
+    }
+
+    pub fn restrict(&mut self, variable_name: &str, variable_value: bool) {
+        let mut manager = self.manager.borrow_mut();
+        let root = manager.restrict(self.root, variable_name, variable_value);
+        manager.reference(root);
+        manager.dereference(self.root);
+        self.root = root;
+        self.variables_cache = OnceCell::new();
+    }
+
+    // Like `restrict`, but for several variables at once and without changing this diagram.
+    #[wasm_bindgen(js_name = cofactor)]
+    pub fn cofactor_js(&self, cube: &JsValue) -> Result<BinaryDecisionDiagram, JsValue> {
+        Ok(self.cofactor(&assignment_from_js(cube)?))
+    }
+
+    // A diagram which agrees with this one wherever `care_set` holds,
+    // the result may depend on variables of `care_set`.
+    pub fn constrain(&self, care_set: &BinaryDecisionDiagram) -> Self {
+        let care = self.root_of(care_set);
+        let root = self.manager.borrow_mut().constrain(self.root, care);
+        self.with_root(root)
+    }
+
+    // A diagram which agrees with this one wherever `care_set` holds and only depends on
+    // variables this one depends on, usually the smaller choice.
+    pub fn restrict_by(&self, care_set: &BinaryDecisionDiagram) -> Self {
+        let care = self.root_of(care_set);
+        let root = self.manager.borrow_mut().restrict_by(self.root, care);
+        self.with_root(root)
+    }
+
+    pub fn exists(&self, variable_name: &str) -> Self {
+        let root = self.manager.borrow_mut().exists(self.root, variable_name);
+        self.with_root(root)
+    }
+
+    pub fn universal(&self, variable_name: &str) -> Self {
+        let root = self
+            .manager
+            .borrow_mut()
+            .universal(self.root, variable_name);
+        self.with_root(root)
+    }
+
+    pub fn exists_many(&self, variables: Vec<String>) -> Self {
+        let root = self.manager.borrow_mut().exists_many(self.root, &variables);
+        self.with_root(root)
+    }
+
+    pub fn universal_many(&self, variables: Vec<String>) -> Self {
+        let root = self
+            .manager
+            .borrow_mut()
+            .universal_many(self.root, &variables);
+        self.with_root(root)
+    }
+
+    pub fn boolean_difference(&self, variable_name: &str) -> Self {
+        let root = self
+            .manager
+            .borrow_mut()
+            .boolean_difference(self.root, variable_name);
+        self.with_root(root)
+    }
+
+    pub fn derivative(&self, variables: Vec<String>) -> Self {
+        let root = self.manager.borrow_mut().derivative(self.root, &variables);
+        self.with_root(root)
+    }
+
+    pub fn unique_exists(&self, variables: Vec<String>) -> Self {
+        let root = self
+            .manager
+            .borrow_mut()
+            .unique_exists(self.root, &variables);
+        self.with_root(root)
+    }
+
+    // `exists variables. self & other`, computed without building the conjunction.
+    pub fn and_exists(&self, other: &BinaryDecisionDiagram, variables: Vec<String>) -> Self {
+        let other_root = self.root_of(other);
+        let root = self
+            .manager
+            .borrow_mut()
+            .and_exists(self.root, other_root, &variables);
+        self.with_root(root)
+    }
+
+    // Variables the function actually depends on, while `variables` of a diagram built from
+    // a formula are all variables of the formula.
+    pub fn support(&self) -> Vec<String> {
+        self.manager
+            .borrow()
+            .support(self.root)
+            .into_iter()
+            .collect()
+    }
+
+    pub fn depends_on(&self, variable_name: &str) -> bool {
+        self.manager.borrow().depends_on(self.root, variable_name)
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.manager.borrow().reachable(self.root).len()
+    }
+
+    // Replaces every occurrence of `variable_name` with `replacement`.
+    pub fn compose(&self, variable_name: &str, replacement: &BinaryDecisionDiagram) -> Self {
+        let replacement = self.root_of(replacement);
+        let root = self
+            .manager
+            .borrow_mut()
+            .compose(self.root, variable_name, replacement);
+        self.with_root(root)
+    }
+
+    // `from[i]` is renamed to `to[i]`, all at once.
+    #[wasm_bindgen(js_name = rename)]
+    pub fn rename_js(&self, from: Vec<String>, to: Vec<String>) -> Result<Self, JsValue> {
+        if from.len() != to.len() {
+            return Err(JsValue::from_str(
+                "every variable needs exactly one new name",
+            ));
+        }
+        Ok(self.rename(&from.into_iter().zip(to).collect()))
+    }
+
+    // `replacements[i]` is substituted for `variables[i]`, all at once.
+    #[wasm_bindgen(js_name = vector_compose)]
+    pub fn vector_compose_js(
+        &self,
+        variables: Vec<String>,
+        replacements: Vec<BinaryDecisionDiagram>,
+    ) -> Result<Self, JsValue> {
+        if variables.len() != replacements.len() {
+            return Err(JsValue::from_str(
+                "every variable needs exactly one replacement",
+            ));
+        }
+        Ok(self.vector_compose(&variables.into_iter().zip(replacements).collect()))
+    }
+
+    pub fn is_satisfiable(&self) -> bool {
+        !self.is_contradiction()
+    }
+
+    pub fn is_tautology(&self) -> bool {
+        self.manager.borrow().terminal_value(self.root) == Some(true)
+    }
+
+    pub fn is_contradiction(&self) -> bool {
+        self.manager.borrow().terminal_value(self.root) == Some(false)
+    }
+
+    // A satisfying assignment, `undefined` if there is none.
+    #[wasm_bindgen(js_name = any_sat)]
+    pub fn any_sat_js(&self) -> JsValue {
+        self.any_sat()
+            .map_or(JsValue::UNDEFINED, |it| assignment_to_js(&it))
+    }
+
+    pub fn equivalent(&self, other: &BinaryDecisionDiagram) -> bool {
+        self.root_of(other) == self.root
+    }
+
+    // An assignment on which the two diagrams differ, `undefined` if they are equivalent.
+    #[wasm_bindgen(js_name = counterexample)]
+    pub fn counterexample_js(&self, other: &BinaryDecisionDiagram) -> JsValue {
+        self.counterexample(other)
+            .map_or(JsValue::UNDEFINED, |it| assignment_to_js(&it))
+    }
+
+    // `assignment` is a plain object mapping variable names to booleans.
+    #[wasm_bindgen(js_name = eval)]
+    pub fn eval_js(&self, assignment: &JsValue) -> Result<bool, JsValue> {
+        self.eval_with(|variable| {
+            js_sys::Reflect::get(assignment, &JsValue::from_str(variable))?
+                .as_bool()
+                .ok_or_else(|| {
+                    JsValue::from_str(&format!("variable `{variable}` is not assigned a boolean"))
+                })
+        })
+    }
+
+    pub fn dot(&self) -> String {
+        Dot::new(&self.manager.borrow().subgraph(self.root)).to_string()
+    }
+
+    // Reorders the variables of every diagram sharing this diagram's manager.
+    pub fn sift(&self) -> ReorderReport {
+        self.manager.borrow_mut().sift()
+    }
+
+    pub fn window_permute(&self, size: usize) -> ReorderReport {
+        self.manager.borrow_mut().window_permute(size)
+    }
+
+    // Sifts automatically once the manager holds more than `threshold` nodes.
+    pub fn enable_auto_reorder(&self, threshold: usize) {
+        self.manager.borrow_mut().set_auto_reorder(Some(threshold));
+    }
+
+    pub fn disable_auto_reorder(&self) {
+        self.manager.borrow_mut().set_auto_reorder(None);
+    }
+
+    pub fn last_reorder(&self) -> Option<ReorderReport> {
+        self.manager.borrow().last_reorder()
+    }
+}
+
+impl BinaryDecisionDiagram {
+    pub fn from_formula(formula: &Expression) -> Self {
+        Self::from_formula_in(&Manager::shared(), formula)
+    }
+
+    // Sets the order of the whole shared manager, not just of the new diagram:
+    // every diagram already in it is reordered in place, keeping its function,
+    // and reports the new order from `variable_order`.
+    pub fn from_formula_with_order(formula: &Expression, order: &VariableOrder) -> Self {
+        Manager::set_shared_order(order.clone());
+        Self::from_formula(formula)
+    }
+
+    pub fn from_formula_in(manager: &Rc<RefCell<Manager>>, formula: &Expression) -> Self {
+        let variables = formula.variables();
+        let mut manager_mut = manager.borrow_mut();
+        manager_mut.add_variables(&variables);
+        let root = formula.to_binary_decision_diagram(&mut manager_mut);
+        drop(manager_mut);
+        let result = Self::from_root(manager.clone(), root);
+        result.variables_cache.set(variables).unwrap();
+        result
+    }
+
+    pub fn constant(value: bool) -> Self {
+        let manager = Manager::shared();
+        let root = manager.borrow().constant(value);
+        Self::from_root(manager, root)
+    }
+
+    pub fn variable(name: &str) -> Self {
+        let manager = Manager::shared();
+        let root = manager.borrow_mut().variable(name);
+        Self::from_root(manager, root)
+    }
+
+    fn from_root(manager: Rc<RefCell<Manager>>, root: NodeIndex) -> Self {
+        {
+            let mut manager = manager.borrow_mut();
+            manager.reference(root);
+            manager.reorder_if_needed();
+            manager.collect_garbage_if_needed();
+        }
+        Self {
+            manager,
+            root,
+            variables_cache: OnceCell::new(),
+        }
+    }
+
+    fn with_root(&self, root: NodeIndex) -> Self {
+        Self::from_root(self.manager.clone(), root)
+    }
+
+    // Follows the edges chosen by `value_of` from the root,
+    // so only the variables on that path are looked up.
+    fn eval_with<E>(&self, mut value_of: impl FnMut(&str) -> Result<bool, E>) -> Result<bool, E> {
+        let manager = self.manager.borrow();
+        let mut node = self.root;
+        loop {
+            if let Some(value) = manager.terminal_value(node) {
+                return Ok(value);
+            }
+            let (false_child, true_child) = manager.children(node);
+            node = if value_of(manager.label(node))? {
+                true_child
+            } else {
+                false_child
+            };
+        }
+    }
+
+    // Returns the name of the first unassigned variable the result depends on, if any.
+    pub fn try_eval(&self, ctx: &HashMap<String, bool>) -> Result<bool, String> {
+        self.eval_with(|variable| {
+            ctx.get(variable)
+                .copied()
+                .ok_or_else(|| variable.to_string())
+        })
+    }
+
+    // Only assigns the variables along one path to the true terminal,
+    // the result holds whatever the other variables are.
+    pub fn any_sat(&self) -> Option<HashMap<String, bool>> {
+        self.manager
+            .borrow()
+            .any_sat(self.root)
+            .map(|it| it.into_iter().collect())
+    }
+
+    // Assigns every variable either diagram depends on, those not needed to tell them apart are false.
+    pub fn counterexample(&self, other: &Self) -> Option<HashMap<String, bool>> {
+        let other_root = self.root_of(other);
+        let mut manager = self.manager.borrow_mut();
+        let difference = manager.apply(|lhs, rhs| lhs ^ rhs, self.root, other_root);
+        let path = manager.any_sat(difference)?;
+        let mut result: HashMap<_, _> = manager
+            .support(self.root)
+            .into_iter()
+            .chain(manager.support(other_root))
+            .map(|it| (it, false))
+            .collect();
+        result.extend(path);
+        drop(manager);
+        debug_assert_ne!(self.eval(&result), other.eval(&result));
+        Some(result)
+    }
+
+    // The root of `other` in this diagram's manager, importing it if it lives elsewhere.
+    fn root_of(&self, other: &Self) -> NodeIndex {
+        if Rc::ptr_eq(&self.manager, &other.manager) {
+            other.root
+        } else {
+            self.manager
+                .borrow_mut()
+                .import(&other.manager.borrow(), other.root)
+        }
+    }
+
+    pub fn cofactor(&self, cube: &HashMap<String, bool>) -> Self {
+        let root = self.manager.borrow_mut().cofactor(self.root, cube);
+        self.with_root(root)
+    }
+
+    // Substitutes every variable in `substitution` simultaneously.
+    pub fn vector_compose(&self, substitution: &HashMap<String, BinaryDecisionDiagram>) -> Self {
+        let substitution = substitution
+            .iter()
+            .map(|(variable, replacement)| (variable.clone(), self.root_of(replacement)))
+            .collect();
+        let root = self
+            .manager
+            .borrow_mut()
+            .vector_compose(self.root, &substitution);
+        self.with_root(root)
+    }
+
+    // Variables missing from `renaming` keep their names.
+    pub fn rename(&self, renaming: &HashMap<String, String>) -> Self {
+        let root = self.manager.borrow_mut().rename(self.root, renaming);
+        self.with_root(root)
+    }
+
+    pub fn apply(&self, other: &Self, f: fn(bool, bool) -> bool) -> Self {
+        self.apply_truth_table(other, manager::truth_table(f))
+    }
+
+    fn apply_truth_table(&self, other: &Self, truth_table: u8) -> Self {
+        let other_root = self.root_of(other);
+        let root = self
+            .manager
+            .borrow_mut()
+            .apply_truth_table(truth_table, self.root, other_root);
+        self.with_root(root)
+    }
+}
+
+#[wasm_bindgen]
+impl BinaryDecisionDiagram {
+    pub fn from_str(code: &str) -> Self {
+        let expr = expression::parse(code).unwrap().1;
+        BinaryDecisionDiagram::from_formula(&expr)
+    }
+
+    // Reorders every diagram in the shared manager, see `from_formula_with_order`.
+    pub fn from_str_with_order(code: &str, order: &VariableOrder) -> Self {
+        let expr = expression::parse(code).unwrap().1;
+        BinaryDecisionDiagram::from_formula_with_order(&expr, order)
+    }
+
+    pub fn from_str_with_heuristic(code: &str, heuristic: OrderingHeuristic) -> Self {
+        let expr = expression::parse(code).unwrap().1;
+        let order = VariableOrder::from_heuristic(&expr, heuristic);
+        BinaryDecisionDiagram::from_formula_with_order(&expr, &order)
+    }
+
+    pub fn compare_heuristics(code: &str) -> Vec<HeuristicResult> {
+        let expr = expression::parse(code).unwrap().1;
+        compare_heuristics(&expr)
+    }
+
+    pub fn equivalent_formulas(lhs: &str, rhs: &str) -> bool {
+        let lhs = expression::parse(lhs).unwrap().1;
+        let rhs = expression::parse(rhs).unwrap().1;
+        lhs.equivalent(&rhs)
+    }
+
+    pub fn formulas_counterexample(lhs: &str, rhs: &str) -> JsValue {
+        let lhs = expression::parse(lhs).unwrap().1;
+        let rhs = expression::parse(rhs).unwrap().1;
+        lhs.counterexample(&rhs)
+            .map_or(JsValue::UNDEFINED, |it| assignment_to_js(&it))
+    }
+
+    pub fn variable_order(&self) -> VariableOrder {
+        self.manager.borrow().order().clone()
+    }
+
+    pub fn or(&self, other: &BinaryDecisionDiagram) -> Self {
+        self.apply(other, |lhs, rhs| lhs || rhs)
+    }
+
+    pub fn and(&self, other: &BinaryDecisionDiagram) -> Self {
+        self.apply(other, |lhs, rhs| lhs && rhs)
+    }
+
+    pub fn not(&self) -> Self {
+        let root = self.manager.borrow_mut().not(self.root);
+        self.with_root(root)
+    }
+
+    // If-then-else: `then` where `condition` holds, `otherwise` elsewhere.
+    pub fn ite(
+        condition: &BinaryDecisionDiagram,
+        then: &BinaryDecisionDiagram,
+        otherwise: &BinaryDecisionDiagram,
+    ) -> Self {
+        let then_root = condition.root_of(then);
+        let otherwise_root = condition.root_of(otherwise);
+        let root = condition
+            .manager
+            .borrow_mut()
+            .ite(condition.root, then_root, otherwise_root);
+        condition.with_root(root)
+    }
+
+    pub fn xor(&self, other: &BinaryDecisionDiagram) -> Self {
+        self.apply(other, |lhs, rhs| lhs ^ rhs)
+    }
+
+    pub fn xnor(&self, other: &BinaryDecisionDiagram) -> Self {
+        self.apply(other, |lhs, rhs| lhs == rhs)
+    }
+
+    pub fn implies(&self, other: &BinaryDecisionDiagram) -> Self {
+        self.apply(other, |lhs, rhs| !lhs || rhs)
+    }
+
+    pub fn nand(&self, other: &BinaryDecisionDiagram) -> Self {
+        self.apply(other, |lhs, rhs| !(lhs && rhs))
+    }
+
+    pub fn nor(&self, other: &BinaryDecisionDiagram) -> Self {
+        self.apply(other, |lhs, rhs| !(lhs || rhs))
+    }
+
+    // Bit `(lhs << 1) | rhs` of `code` is the result for `lhs` and `rhs`,
+    // e.g. 0b1000 is and, 0b0110 is xor.
+    pub fn apply_op(&self, other: &BinaryDecisionDiagram, code: u8) -> Result<Self, JsValue> {
+        if code >= 16 {
+            return Err(JsValue::from_str("operator code must be in 0..16"));
+        }
+        Ok(self.apply_truth_table(other, code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    #[test]
+    fn test_from_formula() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c&d");
+        assert_eq!(bdd.node_count(), 6);
+        let bdd = BinaryDecisionDiagram::from_str("a|!a");
+        assert_eq!(bdd.node_count(), 1);
+        assert_eq!(bdd.root, bdd.manager.borrow().constant(true));
+        let bdd = BinaryDecisionDiagram::from_str("!(a&!(b|0))");
+        assert_eq!(bdd.node_count(), 4);
+        assert_eq!(
+            bdd.variables(),
+            ["a".to_string(), "b".to_string()].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_from_formula_many_variables() {
+        let variables: Vec<String> = ('a'..='z')
+            .map(|c| c.to_string())
+            .chain(('a'..='j').map(|c| format!("z{c}")))
+            .collect();
+        let code = variables.join("&");
+        let bdd = BinaryDecisionDiagram::from_str(&code);
+        assert_eq!(bdd.node_count(), variables.len() + 2);
+    }
+
+    #[test]
+    fn test_shared_nodes() {
+        let lhs = BinaryDecisionDiagram::from_str("a&(b|c)");
+        let rhs = BinaryDecisionDiagram::from_str("a&b|a&c");
+        assert_eq!(lhs.root, rhs.root);
+        let combined = lhs.or(&BinaryDecisionDiagram::from_str("!a"));
+        assert_eq!(
+            combined.root,
+            BinaryDecisionDiagram::from_str("!a|b|c").root
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        let a = BinaryDecisionDiagram::from_str("a");
+        let b = BinaryDecisionDiagram::from_str("b");
+        let cases = [
+            (a.xor(&b), "a&!b|!a&b"),
+            (a.xnor(&b), "a&b|!a&!b"),
+            (a.implies(&b), "!a|b"),
+            (a.nand(&b), "!(a&b)"),
+            (a.nor(&b), "!(a|b)"),
+            (a.not(), "!a"),
+        ];
+        for (bdd, expected) in cases {
+            assert_eq!(bdd.root, BinaryDecisionDiagram::from_str(expected).root);
+        }
+        for code in 0..16 {
+            let bdd = a.apply_op(&b, code).unwrap();
+            for (lhs, rhs) in [(false, false), (false, true), (true, false), (true, true)] {
+                let ctx = [("a".to_string(), lhs), ("b".to_string(), rhs)]
+                    .into_iter()
+                    .collect();
+                let expected = code & (1 << ((lhs as u8) << 1 | rhs as u8)) != 0;
+                assert_eq!(bdd.eval(&ctx), expected);
+            }
+        }
+        assert_eq!(a.apply_op(&b, 0b0110).unwrap().root, a.xor(&b).root);
+        assert_eq!(a.apply_op(&b, 0b1111).unwrap().node_count(), 1);
+        assert_eq!(a.apply_op(&b, 0b1100).unwrap().root, a.root);
+    }
+
+    #[test]
+    fn test_ite() {
+        let s = BinaryDecisionDiagram::from_str("s");
+        let a = BinaryDecisionDiagram::from_str("a&b");
+        let b = BinaryDecisionDiagram::from_str("c|a");
+        let mux = BinaryDecisionDiagram::ite(&s, &a, &b);
+        assert_eq!(
+            mux.root,
+            BinaryDecisionDiagram::from_str("s&a&b|!s&(c|a)").root
+        );
+        let order = ["c", "s", "b", "a"].into_iter().collect();
+        let s = BinaryDecisionDiagram::from_str_with_order("s", &order);
+        let mux = BinaryDecisionDiagram::ite(&s, &a, &b);
+        let expected = BinaryDecisionDiagram::from_str_with_order("s&a&b|!s&(c|a)", &order);
+        assert_eq!(mux.variable_order().variables(), vec!["c", "s", "b", "a"]);
+        assert_eq!(mux.node_count(), expected.node_count());
+        assert!(mux.equivalent(&expected));
+        let t = BinaryDecisionDiagram::constant(true);
+        let f = BinaryDecisionDiagram::constant(false);
+        assert_eq!(BinaryDecisionDiagram::ite(&a, &t, &f).root, a.root);
+        assert_eq!(BinaryDecisionDiagram::ite(&a, &f, &t).root, a.not().root);
+    }
+
+    #[test]
+    fn test_compose() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c");
+        let replacement = BinaryDecisionDiagram::from_str("x|!c");
+        assert_eq!(
+            bdd.compose("a", &replacement).root,
+            BinaryDecisionDiagram::from_str("(x|!c)&b|c").root
+        );
+        assert_eq!(bdd.compose("y", &replacement).root, bdd.root);
+        let separate = BinaryDecisionDiagram::from_formula_in(
+            &Rc::new(RefCell::new(Manager::with_order(
+                ["x", "c"].into_iter().collect(),
+            ))),
+            &expression::parse("x|!c").unwrap().1,
+        );
+        assert_eq!(
+            bdd.compose("a", &separate).root,
+            BinaryDecisionDiagram::from_str("(x|!c)&b|c").root
+        );
+    }
+
+    #[test]
+    fn test_vector_compose() {
+        let bdd = BinaryDecisionDiagram::from_str("a&!b");
+        let substitution = [
+            ("a".to_string(), BinaryDecisionDiagram::from_str("b")),
+            ("b".to_string(), BinaryDecisionDiagram::from_str("a|c")),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            bdd.vector_compose(&substitution).root,
+            BinaryDecisionDiagram::from_str("b&!(a|c)").root
+        );
+        let sequential = bdd
+            .compose("a", &BinaryDecisionDiagram::from_str("b"))
+            .compose("b", &BinaryDecisionDiagram::from_str("a|c"));
+        assert_ne!(sequential.root, bdd.vector_compose(&substitution).root);
+    }
+
+    #[test]
+    fn test_rename() {
+        let order = ["a", "b", "c"].into_iter().collect();
+        let bdd = BinaryDecisionDiagram::from_str_with_order("a&!b|c", &order);
+        let swapped = bdd.rename(
+            &[("a", "c"), ("c", "a")]
+                .map(|(from, to)| (from.to_string(), to.to_string()))
+                .into(),
+        );
+        let expected = BinaryDecisionDiagram::from_str_with_order("c&!b|a", &order);
+        assert!(swapped.equivalent(&expected));
+        assert_eq!(swapped.node_count(), expected.node_count());
+        let primed = bdd.rename(&[("a".to_string(), "a'".to_string())].into());
+        assert_eq!(
+            primed.variable_order().variables(),
+            vec!["a", "b", "c", "a'"]
+        );
+        let mut ctx: HashMap<_, _> = [("a'", true), ("b", false), ("c", false)]
+            .map(|(variable, value)| (variable.to_string(), value))
+            .into();
+        assert!(primed.eval(&ctx));
+        ctx.insert("b".to_string(), true);
+        assert!(!primed.eval(&ctx));
+        assert_eq!(
+            primed
+                .rename(&[("a'".to_string(), "a".to_string())].into())
+                .root,
+            bdd.root
+        );
+    }
+
+    #[test]
+    fn test_restrict_and_quantify() {
+        let mut bdd = BinaryDecisionDiagram::from_str("a&b|c");
+        assert_eq!(
+            bdd.exists("a").root,
+            BinaryDecisionDiagram::from_str("b|c").root
+        );
+        assert_eq!(
+            bdd.universal("a").root,
+            BinaryDecisionDiagram::from_str("c").root
+        );
+        bdd.restrict("c", false);
+        assert_eq!(bdd.root, BinaryDecisionDiagram::from_str("a&b").root);
+    }
+
+    #[test]
+    fn test_support() {
+        let bdd = BinaryDecisionDiagram::from_str("a|b&!b|c&!c&d");
+        assert_eq!(bdd.support(), vec!["a"]);
+        assert!(bdd.depends_on("a"));
+        assert!(!bdd.depends_on("b"));
+        assert!(!bdd.depends_on("x"));
+        let derived = bdd.and(&BinaryDecisionDiagram::from_str("e"));
+        assert_eq!(
+            derived.variables(),
+            ["a".to_string(), "e".to_string()].into_iter().collect()
+        );
+        assert!(BinaryDecisionDiagram::constant(true).variables().is_empty());
+    }
+
+    #[test]
+    fn test_cofactor() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c&d");
+        let cube = [("a".to_string(), true), ("d".to_string(), false)].into();
+        assert_eq!(
+            bdd.cofactor(&cube).root,
+            BinaryDecisionDiagram::from_str("b").root
+        );
+        assert_eq!(bdd.cofactor(&HashMap::new()).root, bdd.root);
+        assert_eq!(bdd.node_count(), 6);
+    }
+
+    #[test]
+    fn test_constrain_and_restrict() {
+        let care_set = BinaryDecisionDiagram::from_str("a|b");
+        let cases = ["a&b|c", "(a|b)&c|!a&!b&d", "a&!b|!a&b", "c"];
+        for code in cases {
+            let bdd = BinaryDecisionDiagram::from_str(code);
+            for result in [bdd.constrain(&care_set), bdd.restrict_by(&care_set)] {
+                assert_eq!(result.and(&care_set).root, bdd.and(&care_set).root);
+            }
+        }
+        let bdd = BinaryDecisionDiagram::from_str("(a|b)&c|!a&!b&d");
+        assert_eq!(
+            bdd.restrict_by(&care_set).root,
+            BinaryDecisionDiagram::from_str("c").root
+        );
+        // constrain may introduce variables of the care set, restrict never does.
+        let bdd = BinaryDecisionDiagram::from_str("c");
+        let care_set = BinaryDecisionDiagram::from_str("a&c|!a&!c");
+        assert_eq!(
+            bdd.constrain(&care_set).root,
+            BinaryDecisionDiagram::from_str("a").root
+        );
+        assert_eq!(bdd.restrict_by(&care_set).root, bdd.root);
+    }
+
+    #[test]
+    fn test_boolean_difference() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c");
+        assert_eq!(
+            bdd.boolean_difference("a").root,
+            BinaryDecisionDiagram::from_str("b&!c").root
+        );
+        assert!(bdd.boolean_difference("x").is_contradiction());
+        let parity = BinaryDecisionDiagram::from_str("a&!b|!a&b");
+        assert!(parity.boolean_difference("b").is_tautology());
+        let variables = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(
+            bdd.derivative(variables.clone()).root,
+            BinaryDecisionDiagram::from_str("!c").root
+        );
+        assert!(parity.derivative(variables).is_contradiction());
+        // The path through !a skips b.
+        let mux = BinaryDecisionDiagram::from_str("a&b|!a&c");
+        assert_eq!(
+            mux.boolean_difference("b").root,
+            BinaryDecisionDiagram::from_str("a").root
+        );
+    }
+
+    #[test]
+    fn test_unique_exists() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c");
+        assert_eq!(
+            bdd.unique_exists(vec!["a".to_string()]).root,
+            BinaryDecisionDiagram::from_str("b&!c").root
+        );
+        // Exactly one of the four assignments to a and b satisfies a&b.
+        let variables = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(
+            bdd.unique_exists(variables.clone()).root,
+            BinaryDecisionDiagram::from_str("!c").root
+        );
+        let bdd = BinaryDecisionDiagram::from_str("a&!b|!a&b&c");
+        assert_eq!(
+            bdd.unique_exists(variables).root,
+            BinaryDecisionDiagram::from_str("!c").root
+        );
+        assert!(bdd.unique_exists(vec!["x".to_string()]).is_contradiction());
+    }
+
+    #[test]
+    fn test_unique_exists_many_variables() {
+        let variables: Vec<_> = (0..24).map(|it| format!("x{it}")).collect();
+        let all = variables
+            .iter()
+            .map(|it| BinaryDecisionDiagram::variable(it))
+            .reduce(|lhs, rhs| lhs.and(&rhs))
+            .unwrap();
+        let parity = variables
+            .iter()
+            .map(|it| BinaryDecisionDiagram::variable(it))
+            .reduce(|lhs, rhs| lhs.xor(&rhs))
+            .unwrap();
+        let y = BinaryDecisionDiagram::variable("y");
+        // A single solution for the x's when y holds, half of all assignments otherwise.
+        let bdd = BinaryDecisionDiagram::ite(&y, &all, &parity);
+        assert!(bdd.unique_exists(variables.clone()).equivalent(&y));
+        let mut fewer = variables;
+        let last = BinaryDecisionDiagram::variable(&fewer.pop().unwrap());
+        assert!(bdd.unique_exists(fewer).equivalent(&y.and(&last)));
+    }
+
+    #[test]
+    fn test_quantify_many() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c&d|e");
+        let variables = vec!["a".to_string(), "c".to_string()];
+        assert_eq!(
+            bdd.exists_many(variables.clone()).root,
+            BinaryDecisionDiagram::from_str("b|d|e").root
+        );
+        assert_eq!(
+            bdd.universal_many(variables.clone()).root,
+            BinaryDecisionDiagram::from_str("e").root
+        );
+        // Image of {s = 0} under the transition relation s' = !s.
+        let state = BinaryDecisionDiagram::from_str("!s");
+        let transition = BinaryDecisionDiagram::from_str("t&!s|!t&s");
+        assert_eq!(
+            state.and_exists(&transition, vec!["s".to_string()]).root,
+            BinaryDecisionDiagram::from_str("t").root
+        );
+        let lhs = BinaryDecisionDiagram::from_str("a&b|c");
+        let rhs = BinaryDecisionDiagram::from_str("!a|d&!c");
+        assert_eq!(
+            lhs.and_exists(&rhs, variables.clone()).root,
+            lhs.and(&rhs).exists_many(variables).root
+        );
+    }
+
+    #[test]
+    fn test_variable_order() {
+        let code = "a&d|b&e|c&f";
+        let interleaved =
+            VariableOrder::new(["a", "d", "b", "e", "c", "f"].map(String::from).to_vec());
+        let bad = BinaryDecisionDiagram::from_str(code);
+        assert_eq!(bad.node_count(), 16);
+        let good = BinaryDecisionDiagram::from_str_with_order(code, &interleaved);
+        assert_eq!(good.node_count(), 8);
+        assert_eq!(good.variable_order(), interleaved);
+        assert_eq!(bad.root, good.root);
+
+        let combined = good.and(&BinaryDecisionDiagram::from_str("f"));
+        assert_eq!(combined.variable_order(), interleaved);
+        let manager = combined.manager.borrow();
+        let (false_child, true_child) = manager.children(combined.root);
+        assert_eq!(manager.label(combined.root), "a");
+        assert_eq!(manager.label(false_child), "b");
+        assert_eq!(manager.label(true_child), "d");
+    }
+
+    #[test]
+    fn test_garbage_collection() {
+        let kept = BinaryDecisionDiagram::from_str("a&b|c");
+        for it in 0..3000 {
+            let variable = BinaryDecisionDiagram::variable(&format!("x{it}"));
+            drop(
+                variable
+                    .and(&kept)
+                    .or(&BinaryDecisionDiagram::variable("y")),
+            );
+        }
+        // Without collection every iteration would leave its nodes behind.
+        assert!(kept.manager.borrow().node_count() < 1 << 13);
+        assert!(kept.equivalent(&BinaryDecisionDiagram::from_str("a&b|c")));
+    }
+
+    #[test]
+    fn test_shared_order() {
+        let existing = BinaryDecisionDiagram::from_str("a&!b|c");
+        let solutions = |bdd: &BinaryDecisionDiagram| -> BTreeSet<Vec<(String, bool)>> {
+            bdd.sat_assignments()
+                .map(|it| {
+                    it.into_iter()
+                        .collect::<BTreeMap<_, _>>()
+                        .into_iter()
+                        .collect()
+                })
+                .collect()
+        };
+        let before = solutions(&existing);
+        let order = VariableOrder::new(["c", "b", "a"].map(String::from).to_vec());
+        let ordered = BinaryDecisionDiagram::from_str_with_order("b|c", &order);
+        assert_eq!(ordered.manager.borrow().label(ordered.root), "c");
+        // The existing diagram is reordered too, but keeps its function.
+        assert_eq!(existing.variable_order(), order);
+        assert_eq!(existing.manager.borrow().label(existing.root), "c");
+        assert_eq!(solutions(&existing), before);
+        assert!(existing.equivalent(&BinaryDecisionDiagram::from_str("c|a&!b")));
+
+        let bdd = BinaryDecisionDiagram::from_str("a&b&c");
+        assert_eq!(bdd.manager.borrow().label(bdd.root), "c");
+        let mut restricted = bdd.exists("c");
+        assert_eq!(restricted.manager.borrow().label(restricted.root), "b");
+        restricted.restrict("b", true);
+        assert_eq!(restricted.root, BinaryDecisionDiagram::from_str("a").root);
+        assert_eq!(restricted.variable_order(), order);
+    }
+
+    #[test]
+    fn test_sift() {
+        let bdd = BinaryDecisionDiagram::from_str("a&d|b&e|c&f");
+        let variables = bdd.variables();
+        let same_function = BinaryDecisionDiagram::from_str("d&a|e&b|f&c");
+        assert_eq!(bdd.node_count(), 16);
+        let report = bdd.sift();
+        assert_eq!(report.before, 16);
+        assert_eq!(report.after, 8);
+        assert_eq!(bdd.last_reorder(), Some(report));
+        assert_eq!(bdd.node_count(), 8);
+        assert_eq!(bdd.variables(), variables);
+        assert_eq!(bdd.root, same_function.root);
+        assert_eq!(
+            bdd.root,
+            BinaryDecisionDiagram::from_str("a&d|b&e|c&f").root
+        );
+    }
+
+    #[test]
+    fn test_auto_reorder() {
+        let bdd = BinaryDecisionDiagram::from_str("a&d|b&e");
+        bdd.enable_auto_reorder(12);
+        let bdd = bdd.or(&BinaryDecisionDiagram::from_str("c&f"));
+        assert!(bdd.last_reorder().is_some());
+        assert_eq!(bdd.node_count(), 8);
+        bdd.disable_auto_reorder();
+    }
+
+    #[test]
+    fn test_eval() {
+        let bdd = BinaryDecisionDiagram::from_str("!(a&b)|c");
+        let mut ctx = HashMap::new();
+        ctx.insert("a".to_string(), true);
+        ctx.insert("b".to_string(), false);
+        ctx.insert("c".to_string(), true);
+        assert!(bdd.eval(&ctx));
+        ctx.insert("b".to_string(), true);
+        assert!(bdd.eval(&ctx));
+        ctx.insert("c".to_string(), false);
+        assert!(!bdd.eval(&ctx));
+
+        let mut partial = HashMap::new();
+        partial.insert("a".to_string(), false);
+        assert_eq!(bdd.try_eval(&partial), Ok(true));
+        partial.insert("a".to_string(), true);
+        assert_eq!(bdd.try_eval(&partial), Err("b".to_string()));
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let lhs = BinaryDecisionDiagram::from_str("!(a&b)");
+        let rhs = BinaryDecisionDiagram::from_str("!a|!b");
+        assert!(lhs.equivalent(&rhs));
+        assert_eq!(lhs.counterexample(&rhs), None);
+
+        let rhs = BinaryDecisionDiagram::from_str("!a&!b|c");
+        assert!(!lhs.equivalent(&rhs));
+        let counterexample = lhs.counterexample(&rhs).unwrap();
+        assert_eq!(counterexample.len(), 3);
+        assert_ne!(lhs.eval(&counterexample), rhs.eval(&counterexample));
+
+        let other_order = VariableOrder::new(["c", "b", "a"].map(String::from).to_vec());
+        let rhs = BinaryDecisionDiagram::from_str_with_order("!b|!a", &other_order);
+        assert!(lhs.equivalent(&rhs));
+    }
+
+    #[test]
+    fn test_satisfiability() {
+        let bdd = BinaryDecisionDiagram::from_str("a&!b");
+        assert!(bdd.is_satisfiable());
+        assert!(!bdd.is_tautology());
+        assert!(!bdd.is_contradiction());
+        let assignment = bdd.any_sat().unwrap();
+        assert_eq!(assignment.len(), 2);
+        assert!(bdd.eval(&assignment));
+
+        let bdd = BinaryDecisionDiagram::from_str("a|b|!a&!b");
+        assert!(bdd.is_satisfiable());
+        assert!(bdd.is_tautology());
+        assert_eq!(bdd.any_sat(), Some(HashMap::new()));
+
+        let bdd = BinaryDecisionDiagram::from_str("a&(b|c)&!a");
+        assert!(!bdd.is_satisfiable());
+        assert!(bdd.is_contradiction());
+        assert_eq!(bdd.any_sat(), None);
+
+        let bdd = BinaryDecisionDiagram::from_str("a|b&c");
+        let assignment = bdd.any_sat().unwrap();
+        assert!(bdd.try_eval(&assignment).unwrap());
+    }
+
+    #[test]
+    fn test_dot() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b");
+        assert_eq!(
+            bdd.dot(),
+            r#"digraph {
+    0 [ label = "a" ]
+    1 [ label = "false" ]
+    2 [ label = "b" ]
+    3 [ label = "true" ]
+    0 -> 1 [ label = "false" ]
+    0 -> 2 [ label = "true" ]
+    2 -> 1 [ label = "false" ]
+    2 -> 3 [ label = "true" ]
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use std::{
+    collections::{hash_map::Entry, BTreeSet, HashMap},
+    fmt::Display,
+};
+
+use petgraph::{
+    stable_graph::{NodeIndex, StableDiGraph},
+    visit::EdgeRef,
+};
+
+use super::manager::VariableId;
+
+// Garbage is not collected automatically before the store holds this many nodes.
+const MIN_COLLECTION_THRESHOLD: usize = 1 << 12;
+
+// Values at the leaves of a diagram.
+pub trait Terminal: Copy + PartialEq + Display {
+    // Terminals with the same key are stored as one node.
+    fn key(self) -> u64;
+}
+
+impl Terminal for bool {
+    fn key(self) -> u64 {
+        self as u64
+    }
+}
+
+impl Terminal for f64 {
+    // 0.0 and -0.0 are one terminal, and so are all NaNs, although neither equals the other.
+    fn key(self) -> u64 {
+        if self == 0.0 {
+            0.0f64.to_bits()
+        } else if self.is_nan() {
+            f64::NAN.to_bits()
+        } else {
+            self.to_bits()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Node<T = bool> {
+    Terminal(T),
+    Variable(VariableId),
+}
+
+// The nodes of every diagram built in a manager, with their unique table and reference counts.
+// Which nodes are redundant, and at which level a variable is, is up to the manager.
+pub struct NodeStore<T> {
+    graph: StableDiGraph<Node<T>, bool>,
+    terminals: HashMap<u64, NodeIndex>,
+    unique_table: HashMap<(VariableId, NodeIndex, NodeIndex), NodeIndex>,
+    // The nodes testing each variable, so a level is visited without scanning the whole store.
+    variable_nodes: HashMap<VariableId, BTreeSet<NodeIndex>>,
+    // Reference counts of the roots held by diagram handles,
+    // nodes unreachable from them are garbage.
+    roots: HashMap<NodeIndex, usize>,
+    collection_threshold: usize,
+}
+
+impl<T: Terminal> Default for NodeStore<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Terminal> NodeStore<T> {
+    pub fn new() -> Self {
+        Self {
+            graph: StableDiGraph::new(),
+            terminals: HashMap::new(),
+            unique_table: HashMap::new(),
+            variable_nodes: HashMap::new(),
+            roots: HashMap::new(),
+            collection_threshold: MIN_COLLECTION_THRESHOLD,
+        }
+    }
+
+    pub fn terminal(&mut self, value: T) -> NodeIndex {
+        *self
+            .terminals
+            .entry(value.key())
+            .or_insert_with(|| self.graph.add_node(Node::Terminal(value)))
+    }
+
+    // The node testing `variable` with these children, which is only created if there is none yet.
+    pub fn make_node(
+        &mut self,
+        variable: VariableId,
+        false_child: NodeIndex,
+        true_child: NodeIndex,
+    ) -> NodeIndex {
+        let key = (variable, false_child, true_child);
+        if let Some(node) = self.unique_table.get(&key) {
+            return *node;
+        }
+        let node = self.graph.add_node(Node::Variable(variable));
+        self.graph.add_edge(node, false_child, false);
+        self.graph.add_edge(node, true_child, true);
+        self.unique_table.insert(key, node);
+        self.variable_nodes
+            .entry(variable)
+            .or_default()
+            .insert(node);
+        node
+    }
+
+    pub fn node(&self, node: NodeIndex) -> Node<T> {
+        self.graph[node]
+    }
+
+    pub fn terminal_value(&self, node: NodeIndex) -> Option<T> {
+        match self.graph[node] {
+            Node::Terminal(value) => Some(value),
+            Node::Variable(_) => None,
+        }
+    }
+
+    pub fn is_terminal(&self, node: NodeIndex) -> bool {
+        self.terminal_value(node).is_some()
+    }
+
+    // The variable a non-terminal node tests.
+    pub fn node_variable(&self, node: NodeIndex) -> VariableId {
+        match self.graph[node] {
+            Node::Variable(id) => id,
+            Node::Terminal(_) => panic!("terminal nodes do not test a variable"),
+        }
+    }
+
+    // Returns `(false_child, true_child)` of a non-terminal node.
+    pub fn children(&self, node: NodeIndex) -> (NodeIndex, NodeIndex) {
+        let mut false_child = None;
+        let mut true_child = None;
+        for edge in self
+            .graph
+            .edges_directed(node, petgraph::Direction::Outgoing)
+        {
+            if *edge.weight() {
+                true_child = Some(edge.target());
+            } else {
+                false_child = Some(edge.target());
+            }
+        }
+        (false_child.unwrap(), true_child.unwrap())
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn nodes_of(&self, variable: VariableId) -> Vec<NodeIndex> {
+        self.variable_nodes
+            .get(&variable)
+            .map_or_else(Vec::new, |it| it.iter().copied().collect())
+    }
+
+    pub fn variable_node_count(&self, variable: VariableId) -> usize {
+        self.variable_nodes.get(&variable).map_or(0, BTreeSet::len)
+    }
+
+    // Rewrites `node` in place, so that handles to it stay valid.
+    // The caller has to make sure the node still represents the same function.
+    pub fn replace_node(
+        &mut self,
+        node: NodeIndex,
+        variable: VariableId,
+        false_child: NodeIndex,
+        true_child: NodeIndex,
+    ) {
+        let (old_false_child, old_true_child) = self.children(node);
+        let old_variable = self.node_variable(node);
+        self.graph[node] = Node::Variable(variable);
+        self.unique_table
+            .remove(&(old_variable, old_false_child, old_true_child));
+        self.forget_variable_node(old_variable, node);
+        self.variable_nodes
+            .entry(variable)
+            .or_default()
+            .insert(node);
+        let edges: Vec<_> = self
+            .graph
+            .edges_directed(node, petgraph::Direction::Outgoing)
+            .map(|it| it.id())
+            .collect();
+        for edge in edges {
+            self.graph.remove_edge(edge);
+        }
+        self.graph.add_edge(node, false_child, false);
+        self.graph.add_edge(node, true_child, true);
+        self.unique_table
+            .insert((variable, false_child, true_child), node);
+    }
+
+    // Nodes reachable from any of `roots`, each listed once.
+    pub fn reachable_from(&self, roots: &[NodeIndex]) -> Vec<NodeIndex> {
+        let mut result = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut stack: Vec<_> = roots.iter().rev().copied().collect();
+        while let Some(node) = stack.pop() {
+            if !visited.insert(node) {
+                continue;
+            }
+            result.push(node);
+            if !self.is_terminal(node) {
+                let (false_child, true_child) = self.children(node);
+                stack.push(true_child);
+                stack.push(false_child);
+            }
+        }
+        result
+    }
+
+    // Copies the diagram rooted at `root` out of the store, labelling each node with `label`.
+    pub fn subgraph(
+        &self,
+        root: NodeIndex,
+        label: impl Fn(NodeIndex) -> String,
+    ) -> StableDiGraph<String, bool> {
+        let nodes = self.reachable_from(&[root]);
+        let mut graph = StableDiGraph::new();
+        let node_map: HashMap<_, _> = nodes
+            .iter()
+            .map(|node| (*node, graph.add_node(label(*node))))
+            .collect();
+        for node in nodes.iter().filter(|it| !self.is_terminal(**it)) {
+            let (false_child, true_child) = self.children(*node);
+            graph.add_edge(node_map[node], node_map[&false_child], false);
+            graph.add_edge(node_map[node], node_map[&true_child], true);
+        }
+        graph
+    }
+
+    pub fn reference(&mut self, node: NodeIndex) {
+        *self.roots.entry(node).or_default() += 1;
+    }
+
+    pub fn dereference(&mut self, node: NodeIndex) {
+        if let Entry::Occupied(mut entry) = self.roots.entry(node) {
+            *entry.get_mut() -= 1;
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+    }
+
+    pub fn live_nodes(&self) -> BTreeSet<NodeIndex> {
+        let mut result = BTreeSet::new();
+        for root in self.roots.keys() {
+            if !result.contains(root) {
+                result.extend(self.reachable_from(&[*root]));
+            }
+        }
+        result
+    }
+
+    // Removes every node unreachable from a referenced root and returns how many were removed.
+    // Indices of removed nodes are reused, so the manager has to forget any it cached.
+    pub fn collect_garbage(&mut self) -> usize {
+        let live_nodes = self.live_nodes();
+        let dead_nodes: Vec<_> = self
+            .graph
+            .node_indices()
+            .filter(|it| !live_nodes.contains(it))
+            .collect();
+        for node in &dead_nodes {
+            match self.graph[*node] {
+                Node::Terminal(value) => {
+                    self.terminals.remove(&value.key());
+                }
+                Node::Variable(variable) => {
+                    let (false_child, true_child) = self.children(*node);
+                    self.unique_table
+                        .remove(&(variable, false_child, true_child));
+                    self.forget_variable_node(variable, *node);
+                }
+            }
+        }
+        for node in &dead_nodes {
+            self.graph.remove_node(*node);
+        }
+        self.collection_threshold = MIN_COLLECTION_THRESHOLD.max(live_nodes.len() * 2);
+        dead_nodes.len()
+    }
+
+    // Removes `node` if neither a root nor another node refers to it,
+    // and then its children the same way.
+    pub fn remove_if_dead(&mut self, node: NodeIndex) {
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            let referenced = self.roots.contains_key(&node)
+                || self
+                    .graph
+                    .edges_directed(node, petgraph::Direction::Incoming)
+                    .next()
+                    .is_some();
+            // A node whose children are the same is pushed twice.
+            if referenced || !self.graph.contains_node(node) {
+                continue;
+            }
+            match self.graph[node] {
+                Node::Terminal(value) => {
+                    self.terminals.remove(&value.key());
+                }
+                Node::Variable(variable) => {
+                    let (false_child, true_child) = self.children(node);
+                    self.unique_table
+                        .remove(&(variable, false_child, true_child));
+                    self.forget_variable_node(variable, node);
+                    stack.push(true_child);
+                    stack.push(false_child);
+                }
+            }
+            self.graph.remove_node(node);
+        }
+    }
+
+    fn forget_variable_node(&mut self, variable: VariableId, node: NodeIndex) {
+        if let Entry::Occupied(mut entry) = self.variable_nodes.entry(variable) {
+            entry.get_mut().remove(&node);
+            if entry.get().is_empty() {
+                entry.remove();
+            }
+        }
+    }
+
+    // Whether the store grew enough since the last collection to collect garbage again.
+    pub fn needs_collection(&self) -> bool {
+        self.graph.node_count() > self.collection_threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_garbage() {
+        let mut store = NodeStore::new();
+        let zero = store.terminal(0.0);
+        let half = store.terminal(0.5);
+        assert_eq!(store.terminal(0.5), half);
+        let kept = store.make_node(0, zero, half);
+        let two = store.terminal(2.0);
+        let dropped = store.make_node(1, zero, two);
+        assert_eq!(store.make_node(0, zero, half), kept);
+        store.reference(kept);
+        store.reference(dropped);
+        store.dereference(dropped);
+        assert_eq!(store.collect_garbage(), 2);
+        assert_eq!(store.reachable_from(&[kept]), vec![kept, zero, half]);
+        assert_eq!(store.node_count(), 3);
+        let two = store.terminal(2.0);
+        let recreated = store.make_node(1, zero, two);
+        assert_eq!(store.terminal_value(store.children(recreated).1), Some(2.0));
+        store.dereference(kept);
+        assert_eq!(store.collect_garbage(), 5);
+        assert_eq!(store.node_count(), 0);
+    }
+    #[test]
+    fn test_float_terminals() {
+        let mut store = NodeStore::new();
+        assert_eq!(store.terminal(0.0), store.terminal(-0.0));
+        let nan = store.terminal(f64::NAN);
+        assert_eq!(store.terminal(-f64::NAN), nan);
+        assert_eq!(store.terminal(f64::INFINITY - f64::INFINITY), nan);
+        assert_ne!(store.terminal(1.0), nan);
+        assert_eq!(store.node_count(), 3);
+    }
+}
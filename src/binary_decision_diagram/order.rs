@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+// Position of each variable from the root (level 0) towards the terminals.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VariableOrder {
+    variables: Vec<String>,
+    levels: HashMap<String, usize>,
+}
+
+impl VariableOrder {
+    pub fn level(&self, variable: &str) -> Option<usize> {
+        self.levels.get(variable).copied()
+    }
+
+    pub fn variable_at(&self, level: usize) -> &str {
+        &self.variables[level]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.variables.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.variables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty()
+    }
+
+    // Appends `variable` after all known variables, unless it is already ordered.
+    pub fn push(&mut self, variable: &str) -> usize {
+        if let Some(level) = self.level(variable) {
+            return level;
+        }
+        let level = self.variables.len();
+        self.variables.push(variable.to_string());
+        self.levels.insert(variable.to_string(), level);
+        level
+    }
+
+    // Exchanges the variables at `level` and `level + 1`.
+    pub fn swap(&mut self, level: usize) {
+        self.variables.swap(level, level + 1);
+        self.levels.insert(self.variables[level].clone(), level);
+        self.levels
+            .insert(self.variables[level + 1].clone(), level + 1);
+    }
+}
+
+impl<S> FromIterator<S> for VariableOrder
+where
+    S: AsRef<str>,
+{
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        let mut result = Self::default();
+        for variable in iter {
+            result.push(variable.as_ref());
+        }
+        result
+    }
+}
+
+#[wasm_bindgen]
+impl VariableOrder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(variables: Vec<String>) -> Self {
+        variables.into_iter().collect()
+    }
+
+    pub fn variables(&self) -> Vec<String> {
+        self.variables.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_order() {
+        let mut order: VariableOrder = ["x2", "x10", "x2", "x1"].into_iter().collect();
+        assert_eq!(order.len(), 3);
+        assert_eq!(order.level("x2"), Some(0));
+        assert_eq!(order.level("x10"), Some(1));
+        assert_eq!(order.level("x1"), Some(2));
+        assert_eq!(order.level("x3"), None);
+        assert_eq!(order.push("x3"), 3);
+        assert_eq!(order.push("x10"), 1);
+        assert_eq!(order.variable_at(3), "x3");
+        assert_eq!(order.variables(), vec!["x2", "x10", "x1", "x3"]);
+        order.swap(1);
+        assert_eq!(order.level("x1"), Some(1));
+        assert_eq!(order.level("x10"), Some(2));
+        assert_eq!(order.variables(), vec!["x2", "x1", "x10", "x3"]);
+    }
+}
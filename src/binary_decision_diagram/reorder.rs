@@ -0,0 +1,316 @@
+use petgraph::stable_graph::NodeIndex;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use super::manager::{Manager, VariableId};
+use super::node_store::Node;
+use super::order::VariableOrder;
+
+// Sifting stops moving a variable in one direction once the diagram grows past this factor.
+const MAX_GROWTH: f64 = 1.2;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReorderReport {
+    pub before: usize,
+    pub after: usize,
+}
+
+// Adjacent transpositions which walk through all permutations of `n` elements,
+// `i` stands for exchanging the elements at `i` and `i + 1`.
+fn plain_changes(n: usize) -> Vec<usize> {
+    if n <= 1 {
+        return Vec::new();
+    }
+    let inner = plain_changes(n - 1);
+    let mut result = Vec::new();
+    let mut at_right = true;
+    for step in 0..=inner.len() {
+        if at_right {
+            result.extend((0..n - 1).rev());
+        } else {
+            result.extend(0..n - 1);
+        }
+        at_right = !at_right;
+        if let Some(position) = inner.get(step) {
+            result.push(if at_right { *position } else { position + 1 });
+        }
+    }
+    result
+}
+
+impl Manager {
+    fn cofactors(&self, node: NodeIndex, variable: VariableId) -> (NodeIndex, NodeIndex) {
+        if self.node(node) == Node::Variable(variable) {
+            self.children(node)
+        } else {
+            (node, node)
+        }
+    }
+
+    // Exchanges the variables at `level` and `level + 1` in place,
+    // every node keeps representing the same function.
+    // Nodes only the rewritten ones referred to are removed right away,
+    // so the store does not grow with garbage while sifting.
+    pub fn swap_adjacent_levels(&mut self, level: usize) {
+        let upper = self.variable_at(level);
+        let lower = self.variable_at(level + 1);
+        self.swap_order(level);
+        for node in self.nodes_of(upper) {
+            let (false_child, true_child) = self.children(node);
+            let depends_on_lower = [false_child, true_child]
+                .into_iter()
+                .any(|it| self.node(it) == Node::Variable(lower));
+            if !depends_on_lower {
+                continue;
+            }
+            let (false_false, false_true) = self.cofactors(false_child, lower);
+            let (true_false, true_true) = self.cofactors(true_child, lower);
+            let new_false_child = self.make_node(upper, false_false, true_false);
+            let new_true_child = self.make_node(upper, false_true, true_true);
+            self.replace_node(node, lower, new_false_child, new_true_child);
+            self.remove_if_dead(false_child);
+            self.remove_if_dead(true_child);
+        }
+    }
+
+    fn move_variable(&mut self, from: usize, to: usize) {
+        if from < to {
+            for level in from..to {
+                self.swap_adjacent_levels(level);
+            }
+        } else {
+            for level in (to..from).rev() {
+                self.swap_adjacent_levels(level);
+            }
+        }
+    }
+
+    // Reorders the variables in place so that those in `order` come first, in that order.
+    pub fn set_order(&mut self, order: &VariableOrder) {
+        for (target, variable) in order.iter().enumerate() {
+            let id = self.variable_id(variable);
+            self.move_variable(self.variable_level(id), target);
+        }
+        self.clear_computed_cache();
+    }
+
+    // Rudell's sifting: each variable is moved through every level and left where
+    // the live node count was the smallest.
+    // After the first collection every node is live, and swaps keep it that way,
+    // so the node count is the live node count.
+    pub fn sift(&mut self) -> ReorderReport {
+        self.collect_garbage();
+        let before = self.node_count();
+        let mut variables: Vec<_> = (0..self.order().len())
+            .map(|level| {
+                let variable = self.variable_at(level);
+                (self.variable_node_count(variable), variable)
+            })
+            .collect();
+        variables.sort_by(|lhs, rhs| rhs.cmp(lhs));
+        let level_count = self.order().len();
+        for (_, variable) in variables {
+            let start = self.variable_level(variable);
+            let mut best = (self.node_count(), start);
+            let mut level = start;
+            let directions = if start < level_count / 2 {
+                [false, true]
+            } else {
+                [true, false]
+            };
+            for downwards in directions {
+                loop {
+                    if downwards && level + 1 < level_count {
+                        self.swap_adjacent_levels(level);
+                        level += 1;
+                    } else if !downwards && level > 0 {
+                        self.swap_adjacent_levels(level - 1);
+                        level -= 1;
+                    } else {
+                        break;
+                    }
+                    let size = self.node_count();
+                    if size < best.0 {
+                        best = (size, level);
+                    }
+                    if size as f64 > best.0 as f64 * MAX_GROWTH {
+                        break;
+                    }
+                }
+            }
+            self.move_variable(level, best.1);
+        }
+        self.clear_computed_cache();
+        let report = ReorderReport {
+            before,
+            after: self.node_count(),
+        };
+        self.last_reorder = Some(report);
+        report
+    }
+
+    // Tries every permutation of each `size` adjacent levels,
+    // and repeats until no window can be improved.
+    // Windows smaller than two levels have nothing to permute and leave the order as it is.
+    pub fn window_permute(&mut self, size: usize) -> ReorderReport {
+        self.collect_garbage();
+        let before = self.node_count();
+        let size = size.min(self.order().len());
+        let swaps = plain_changes(size);
+        let mut improved = size >= 2;
+        while improved {
+            improved = false;
+            for start in 0..=self.order().len() - size {
+                let mut best = (self.node_count(), 0);
+                for (step, position) in swaps.iter().enumerate() {
+                    self.swap_adjacent_levels(start + position);
+                    let size = self.node_count();
+                    if size < best.0 {
+                        best = (size, step + 1);
+                    }
+                }
+                for position in swaps[best.1..].iter().rev() {
+                    self.swap_adjacent_levels(start + position);
+                }
+                improved |= best.1 != 0;
+            }
+        }
+        self.clear_computed_cache();
+        let report = ReorderReport {
+            before,
+            after: self.node_count(),
+        };
+        self.last_reorder = Some(report);
+        report
+    }
+
+    // `None` disables automatic reordering.
+    pub fn set_auto_reorder(&mut self, threshold: Option<usize>) {
+        self.auto_reorder_threshold = threshold;
+    }
+
+    pub fn last_reorder(&self) -> Option<ReorderReport> {
+        self.last_reorder
+    }
+
+    // Called between operations, when every node in use is held by a referenced root.
+    pub fn reorder_if_needed(&mut self) {
+        let Some(threshold) = self.auto_reorder_threshold else {
+            return;
+        };
+        if self.node_count() <= threshold {
+            return;
+        }
+        self.collect_garbage();
+        if self.live_node_count() > threshold {
+            let report = self.sift();
+            self.auto_reorder_threshold = Some(threshold.max(report.after * 2));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    #[test]
+    fn test_plain_changes() {
+        assert_eq!(plain_changes(2), vec![0]);
+        assert_eq!(plain_changes(3), vec![1, 0, 1, 0, 1]);
+        for n in 2..=5 {
+            let mut permutation: Vec<usize> = (0..n).collect();
+            let mut seen = BTreeSet::new();
+            seen.insert(permutation.clone());
+            for position in plain_changes(n) {
+                permutation.swap(position, position + 1);
+                seen.insert(permutation.clone());
+            }
+            assert_eq!(seen.len(), (1..=n).product::<usize>());
+        }
+    }
+
+    fn pairs(manager: &mut Manager) -> NodeIndex {
+        // a&d | b&e | c&f
+        let mut result = manager.constant(false);
+        for (x, y) in [("a", "d"), ("b", "e"), ("c", "f")] {
+            let x = manager.variable(x);
+            let y = manager.variable(y);
+            let x_and_y = manager.apply(|x, y| x && y, x, y);
+            result = manager.apply(|x, y| x || y, result, x_and_y);
+        }
+        result
+    }
+
+    #[test]
+    fn test_swap_keeps_functions() {
+        let mut manager = Manager::with_order(["a", "b", "c", "d", "e", "f"].into_iter().collect());
+        let root = pairs(&mut manager);
+        manager.reference(root);
+        let expected = manager.subgraph(root).node_count();
+        for level in [0, 2, 4, 1, 3, 0] {
+            manager.swap_adjacent_levels(level);
+        }
+        manager.set_order(&["a", "b", "c", "d", "e", "f"].into_iter().collect());
+        manager.collect_garbage();
+        assert_eq!(manager.reachable(root).len(), expected);
+        assert_eq!(root, pairs(&mut manager));
+    }
+
+    #[test]
+    fn test_sift() {
+        let mut manager = Manager::with_order(["a", "b", "c", "d", "e", "f"].into_iter().collect());
+        let root = pairs(&mut manager);
+        manager.reference(root);
+        let report = manager.sift();
+        assert_eq!(report.before, 16);
+        assert_eq!(report.after, 8);
+        assert_eq!(manager.last_reorder(), Some(report));
+        assert_eq!(manager.reachable(root).len(), 8);
+        assert_eq!(manager.node_count(), 8);
+        assert_eq!(root, pairs(&mut manager));
+    }
+
+    #[test]
+    fn test_window_permute() {
+        let mut manager = Manager::with_order(["a", "d", "b", "c", "e", "f"].into_iter().collect());
+        let root = pairs(&mut manager);
+        manager.reference(root);
+        let report = manager.window_permute(3);
+        assert!(report.after < report.before);
+        assert_eq!(manager.live_node_count(), report.after);
+        assert_eq!(root, pairs(&mut manager));
+    }
+
+    #[test]
+    fn test_window_too_small() {
+        let mut manager = Manager::with_order(["a", "b", "c", "d", "e", "f"].into_iter().collect());
+        let root = pairs(&mut manager);
+        manager.reference(root);
+        for size in [0, 1] {
+            let report = manager.window_permute(size);
+            assert_eq!(report.before, report.after);
+        }
+        let mut manager = Manager::with_order(["a"].into_iter().collect());
+        let root = manager.variable("a");
+        manager.reference(root);
+        assert_eq!(
+            manager.window_permute(3),
+            ReorderReport {
+                before: 3,
+                after: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_auto_reorder() {
+        let mut manager = Manager::with_order(["a", "b", "c", "d", "e", "f"].into_iter().collect());
+        manager.set_auto_reorder(Some(10));
+        let root = pairs(&mut manager);
+        manager.reference(root);
+        manager.reorder_if_needed();
+        assert_eq!(manager.last_reorder().unwrap().after, 8);
+        assert_eq!(manager.live_node_count(), 8);
+    }
+}
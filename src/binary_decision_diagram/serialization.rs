@@ -0,0 +1,543 @@
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use petgraph::stable_graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
+
+use super::{BinaryDecisionDiagram, Manager, VariableOrder};
+use crate::ContainVariable;
+
+const MAGIC: &[u8] = b"BDD\x01";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct SerializedNode {
+    // Position of the variable in `order`.
+    variable: usize,
+    false_child: usize,
+    true_child: usize,
+}
+
+// Nodes 0 and 1 are the false and true terminals, the others follow with children first.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct SerializedDiagram {
+    order: Vec<String>,
+    nodes: Vec<SerializedNode>,
+    root: usize,
+}
+
+fn write_varint(output: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<usize, String> {
+    let mut result = 0usize;
+    for shift in (0..usize::BITS).step_by(7) {
+        let (byte, rest) = input
+            .split_first()
+            .ok_or_else(|| "unexpected end of input".to_string())?;
+        *input = rest;
+        let payload = (byte & 0x7f) as usize;
+        // The last group only has room for the low bits of `payload`.
+        let shifted = payload
+            .checked_shl(shift)
+            .filter(|it| it >> shift == payload)
+            .ok_or_else(|| "integer too large".to_string())?;
+        result |= shifted;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err("integer too large".to_string())
+}
+
+impl SerializedDiagram {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut result = MAGIC.to_vec();
+        write_varint(&mut result, self.order.len());
+        for variable in &self.order {
+            write_varint(&mut result, variable.len());
+            result.extend(variable.as_bytes());
+        }
+        write_varint(&mut result, self.nodes.len());
+        for node in &self.nodes {
+            write_varint(&mut result, node.variable);
+            write_varint(&mut result, node.false_child);
+            write_varint(&mut result, node.true_child);
+        }
+        write_varint(&mut result, self.root);
+        result
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut input = bytes
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| "not a serialized diagram".to_string())?;
+        let variable_count = read_varint(&mut input)?;
+        let mut order = Vec::new();
+        for _ in 0..variable_count {
+            let length = read_varint(&mut input)?;
+            if input.len() < length {
+                return Err("unexpected end of input".to_string());
+            }
+            let (name, rest) = input.split_at(length);
+            order.push(String::from_utf8(name.to_vec()).map_err(|it| it.to_string())?);
+            input = rest;
+        }
+        let node_count = read_varint(&mut input)?;
+        let mut nodes = Vec::new();
+        for _ in 0..node_count {
+            nodes.push(SerializedNode {
+                variable: read_varint(&mut input)?,
+                false_child: read_varint(&mut input)?,
+                true_child: read_varint(&mut input)?,
+            });
+        }
+        let root = read_varint(&mut input)?;
+        if !input.is_empty() {
+            return Err("trailing bytes after the diagram".to_string());
+        }
+        Ok(Self { order, nodes, root })
+    }
+}
+
+impl BinaryDecisionDiagram {
+    // The manager's order restricted to the variables of this diagram.
+    fn own_order(&self) -> Vec<String> {
+        let variables = self.variables();
+        self.manager
+            .borrow()
+            .order()
+            .iter()
+            .filter(|it| variables.contains(*it))
+            .cloned()
+            .collect()
+    }
+
+    fn to_serialized(&self) -> SerializedDiagram {
+        let order = self.own_order();
+        let positions: HashMap<_, _> = order
+            .iter()
+            .enumerate()
+            .map(|(position, variable)| (variable.as_str(), position))
+            .collect();
+        let manager = self.manager.borrow();
+        let mut nodes: Vec<_> = manager
+            .reachable(self.root)
+            .into_iter()
+            .filter(|it| !manager.is_terminal(*it))
+            .collect();
+        nodes.sort_by_key(|it| Reverse(manager.level(*it)));
+        let mut indices: HashMap<_, _> =
+            [(manager.constant(false), 0), (manager.constant(true), 1)]
+                .into_iter()
+                .collect();
+        for (index, node) in nodes.iter().enumerate() {
+            indices.insert(*node, index + 2);
+        }
+        let nodes = nodes
+            .iter()
+            .map(|node| {
+                let (false_child, true_child) = manager.children(*node);
+                SerializedNode {
+                    variable: positions[manager.label(*node)],
+                    false_child: indices[&false_child],
+                    true_child: indices[&true_child],
+                }
+            })
+            .collect();
+        SerializedDiagram {
+            order,
+            nodes,
+            root: indices[&self.root],
+        }
+    }
+
+    // Rebuilds the diagram in a new manager, rejecting it unless it is ordered and reduced.
+    fn from_serialized(serialized: &SerializedDiagram) -> Result<Self, String> {
+        let order: VariableOrder = serialized.order.iter().collect();
+        if order.len() != serialized.order.len() {
+            return Err("the variable order contains duplicates".to_string());
+        }
+        let mut manager = Manager::with_order(order);
+        let mut built = vec![manager.constant(false), manager.constant(true)];
+        // Position of the variable of each built node, terminals come after every variable.
+        let mut positions = vec![usize::MAX, usize::MAX];
+        let mut seen = HashSet::new();
+        for (index, node) in serialized.nodes.iter().enumerate() {
+            let index = index + 2;
+            let Some(variable) = serialized.order.get(node.variable) else {
+                return Err(format!("node {index} uses an unknown variable"));
+            };
+            if node.false_child >= index || node.true_child >= index {
+                return Err(format!("node {index} refers to a node after it"));
+            }
+            if positions[node.false_child] <= node.variable
+                || positions[node.true_child] <= node.variable
+            {
+                return Err(format!("node {index} is not ordered"));
+            }
+            if node.false_child == node.true_child {
+                return Err(format!("node {index} is redundant"));
+            }
+            if !seen.insert((node.variable, node.false_child, node.true_child)) {
+                return Err(format!("node {index} is a duplicate"));
+            }
+            let id = manager.variable_id(variable);
+            built.push(manager.make_node(id, built[node.false_child], built[node.true_child]));
+            positions.push(node.variable);
+        }
+        let root = *built
+            .get(serialized.root)
+            .ok_or_else(|| "the root does not exist".to_string())?;
+        let result = Self::from_root(Rc::new(RefCell::new(manager)), root);
+        result
+            .variables_cache
+            .set(serialized.order.iter().cloned().collect())
+            .unwrap();
+        Ok(result)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Self::from_serialized(&SerializedDiagram::from_bytes(bytes)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let serialized = serde_json::from_str(json).map_err(|it| it.to_string())?;
+        Self::from_serialized(&serialized)
+    }
+
+    // Reads the first root of a diagram in DDDMP text format, as written by CUDD.
+    pub fn from_dddmp(text: &str) -> Result<Self, String> {
+        let mut header = HashMap::new();
+        let mut lines = text.lines().map(str::trim).filter(|it| !it.is_empty());
+        for line in lines.by_ref() {
+            if line == ".nodes" {
+                break;
+            }
+            let mut fields = line.split_whitespace();
+            let key = fields.next().unwrap();
+            header.insert(key, fields.collect::<Vec<_>>());
+        }
+        let support = header
+            .get(".suppvarnames")
+            .ok_or_else(|| "missing .suppvarnames".to_string())?;
+        let order = header.get(".orderedvarnames").unwrap_or(support);
+        let variable_order: VariableOrder = order.iter().collect();
+        if variable_order.len() != order.len() {
+            return Err("the variable order contains duplicates".to_string());
+        }
+        if let Some(variable) = support.iter().find(|it| !order.contains(it)) {
+            return Err(format!("variable `{variable}` is missing from the order"));
+        }
+        let mut manager = Manager::with_order(variable_order);
+        let mut built = HashMap::new();
+        let node_of = |manager: &mut Manager, built: &HashMap<i64, NodeIndex>, reference: i64| {
+            let node = *built
+                .get(&reference.abs())
+                .ok_or_else(|| format!("node {reference} is used before it is defined"))?;
+            Ok::<_, String>(if reference < 0 {
+                manager.not(node)
+            } else {
+                node
+            })
+        };
+        for line in lines.take_while(|it| *it != ".end") {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let [id, info, index, then, otherwise] = fields[..] else {
+                return Err(format!("malformed node `{line}`"));
+            };
+            let parse = |field: &str| {
+                field
+                    .parse::<i64>()
+                    .map_err(|_| format!("malformed node `{line}`"))
+            };
+            let node = if info == "T" {
+                manager.constant(true)
+            } else {
+                let variable = support
+                    .get(parse(index)? as usize)
+                    .ok_or_else(|| format!("node {id} uses an unknown variable"))?;
+                let variable = manager.variable(variable);
+                let then = node_of(&mut manager, &built, parse(then)?)?;
+                let otherwise = node_of(&mut manager, &built, parse(otherwise)?)?;
+                let level = manager.level(variable);
+                if manager.level(then) <= level || manager.level(otherwise) <= level {
+                    return Err(format!("node {id} is not ordered"));
+                }
+                manager.ite(variable, then, otherwise)
+            };
+            built.insert(parse(id)?, node);
+        }
+        let root = header
+            .get(".rootids")
+            .and_then(|it| it.first())
+            .ok_or_else(|| "missing .rootids".to_string())?
+            .parse::<i64>()
+            .map_err(|it| it.to_string())?;
+        let root = node_of(&mut manager, &built, root)?;
+        Ok(Self::from_root(Rc::new(RefCell::new(manager)), root))
+    }
+}
+
+#[wasm_bindgen]
+impl BinaryDecisionDiagram {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_serialized().to_bytes()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_serialized()).unwrap()
+    }
+
+    // DDDMP has a single terminal and complemented edges, an edge to false is written as `-1`.
+    pub fn to_dddmp(&self) -> String {
+        fn reference(
+            manager: &Manager,
+            node: NodeIndex,
+            positions: &HashMap<&str, usize>,
+            nodes: &mut Vec<(usize, i64, i64)>,
+            ids: &mut HashMap<(usize, i64, i64), i64>,
+            referenced: &mut HashMap<NodeIndex, i64>,
+        ) -> i64 {
+            if let Some(value) = manager.terminal_value(node) {
+                return if value { 1 } else { -1 };
+            }
+            if let Some(result) = referenced.get(&node) {
+                return *result;
+            }
+            let (false_child, true_child) = manager.children(node);
+            let otherwise = reference(manager, false_child, positions, nodes, ids, referenced);
+            let then = reference(manager, true_child, positions, nodes, ids, referenced);
+            let variable = positions[manager.label(node)];
+            // Then edges are never complemented, so the complement of the node is stored instead.
+            let (key, sign) = if then < 0 {
+                ((variable, -then, -otherwise), -1)
+            } else {
+                ((variable, then, otherwise), 1)
+            };
+            let id = *ids.entry(key).or_insert_with(|| {
+                nodes.push(key);
+                nodes.len() as i64 + 1
+            });
+            referenced.insert(node, sign * id);
+            sign * id
+        }
+        let order = self.own_order();
+        let manager = self.manager.borrow();
+        let support: Vec<_> = order
+            .iter()
+            .filter(|it| manager.support(self.root).contains(*it))
+            .collect();
+        let positions: HashMap<_, _> = support
+            .iter()
+            .enumerate()
+            .map(|(position, variable)| (variable.as_str(), position))
+            .collect();
+        let mut nodes = Vec::new();
+        let root = reference(
+            &manager,
+            self.root,
+            &positions,
+            &mut nodes,
+            &mut HashMap::new(),
+            &mut HashMap::new(),
+        );
+        let ids: Vec<_> = support
+            .iter()
+            .map(|variable| order.iter().position(|it| it == *variable).unwrap())
+            .collect();
+        let join = |items: &mut dyn Iterator<Item = String>| items.collect::<Vec<_>>().join(" ");
+        let mut result = vec![
+            ".ver DDDMP-2.0".to_string(),
+            ".mode A".to_string(),
+            ".varinfo 0".to_string(),
+            format!(".nnodes {}", nodes.len() + 1),
+            format!(".nvars {}", order.len()),
+            format!(".nsuppvars {}", support.len()),
+            format!(
+                ".suppvarnames {}",
+                join(&mut support.iter().map(|it| it.to_string()))
+            ),
+            format!(".orderedvarnames {}", order.join(" ")),
+            format!(".ids {}", join(&mut ids.iter().map(|it| it.to_string()))),
+            format!(
+                ".permids {}",
+                join(&mut ids.iter().map(|it| it.to_string()))
+            ),
+            ".nroots 1".to_string(),
+            format!(".rootids {root}"),
+            ".nodes".to_string(),
+            "1 T 1 0 0".to_string(),
+        ];
+        for (index, (variable, then, otherwise)) in nodes.into_iter().enumerate() {
+            result.push(format!(
+                "{} {} {variable} {then} {otherwise}",
+                index + 2,
+                ids[variable]
+            ));
+        }
+        result.push(".end".to_string());
+        result.join("\n") + "\n"
+    }
+
+    #[wasm_bindgen(js_name = from_bytes)]
+    pub fn from_bytes_js(bytes: &[u8]) -> Result<BinaryDecisionDiagram, JsValue> {
+        Self::from_bytes(bytes).map_err(|it| JsValue::from_str(&it))
+    }
+
+    #[wasm_bindgen(js_name = from_json)]
+    pub fn from_json_js(json: &str) -> Result<BinaryDecisionDiagram, JsValue> {
+        Self::from_json(json).map_err(|it| JsValue::from_str(&it))
+    }
+
+    #[wasm_bindgen(js_name = from_dddmp)]
+    pub fn from_dddmp_js(text: &str) -> Result<BinaryDecisionDiagram, JsValue> {
+        Self::from_dddmp(text).map_err(|it| JsValue::from_str(&it))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same(lhs: &BinaryDecisionDiagram, rhs: &BinaryDecisionDiagram) {
+        assert!(lhs.equivalent(rhs));
+        assert_eq!(lhs.node_count(), rhs.node_count());
+        assert_eq!(lhs.variables(), rhs.variables());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let order = ["c", "a", "b", "d"].into_iter().collect();
+        let bdd = BinaryDecisionDiagram::from_str_with_order("a&b|c&!d", &order);
+        let loaded = BinaryDecisionDiagram::from_bytes(&bdd.to_bytes()).unwrap();
+        assert_same(&bdd, &loaded);
+        assert_eq!(loaded.variable_order(), order);
+        let bytes = bdd.to_bytes();
+        assert!(BinaryDecisionDiagram::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(BinaryDecisionDiagram::from_bytes(b"{}").is_err());
+        let constant = BinaryDecisionDiagram::constant(true);
+        let loaded = BinaryDecisionDiagram::from_bytes(&constant.to_bytes()).unwrap();
+        assert!(loaded.is_tautology());
+    }
+
+    #[test]
+    fn test_varint() {
+        let mut output = Vec::new();
+        write_varint(&mut output, usize::MAX);
+        assert_eq!(read_varint(&mut output.as_slice()), Ok(usize::MAX));
+        let mut overlong = vec![0xff; (usize::BITS / 7) as usize];
+        overlong.push(0x7f);
+        assert!(read_varint(&mut overlong.as_slice()).is_err());
+        let mut too_many_groups = vec![0x80; (usize::BITS / 7 + 1) as usize];
+        too_many_groups.push(0x01);
+        assert!(read_varint(&mut too_many_groups.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|!a&c");
+        let loaded = BinaryDecisionDiagram::from_json(&bdd.to_json()).unwrap();
+        assert_same(&bdd, &loaded);
+        let json = r#"{"order": ["a", "b"], "nodes": [
+            {"variable": 1, "false_child": 0, "true_child": 1},
+            {"variable": 0, "false_child": 0, "true_child": 2}
+        ], "root": 3}"#;
+        let loaded = BinaryDecisionDiagram::from_json(json).unwrap();
+        assert!(loaded.equivalent(&BinaryDecisionDiagram::from_str("a&b")));
+    }
+
+    #[test]
+    fn test_validation() {
+        let load = |nodes: &str, root: usize| {
+            let json = format!(r#"{{"order": ["a", "b"], "nodes": [{nodes}], "root": {root}}}"#);
+            BinaryDecisionDiagram::from_json(&json).map(|_| ())
+        };
+        let b = r#"{"variable": 1, "false_child": 0, "true_child": 1}"#;
+        let a = r#"{"variable": 0, "false_child": 0, "true_child": 1}"#;
+        let a_over_a = r#"{"variable": 0, "false_child": 0, "true_child": 2}"#;
+        let b_over_a = r#"{"variable": 1, "false_child": 0, "true_child": 2}"#;
+        let redundant = r#"{"variable": 0, "false_child": 2, "true_child": 2}"#;
+        assert!(load(&format!("{b}, {a_over_a}"), 3).is_ok());
+        assert!(load(&format!("{a}, {b_over_a}"), 3)
+            .unwrap_err()
+            .contains("not ordered"));
+        assert!(load(&format!("{a}, {a_over_a}"), 3)
+            .unwrap_err()
+            .contains("not ordered"));
+        assert!(load(&format!("{b}, {redundant}"), 3)
+            .unwrap_err()
+            .contains("redundant"));
+        assert!(load(&format!("{b}, {b}"), 3)
+            .unwrap_err()
+            .contains("duplicate"));
+        assert!(load(b, 5).is_err());
+        assert!(BinaryDecisionDiagram::from_json("[]").is_err());
+    }
+
+    #[test]
+    fn test_dddmp() {
+        let bdd = BinaryDecisionDiagram::from_str("a&!b|!a&c");
+        let text = bdd.to_dddmp();
+        assert!(text.starts_with(".ver DDDMP-2.0\n"));
+        assert!(text.contains(".suppvarnames a b c\n"));
+        let loaded = BinaryDecisionDiagram::from_dddmp(&text).unwrap();
+        assert!(loaded.equivalent(&bdd));
+        assert_eq!(loaded.node_count(), bdd.node_count());
+
+        // !(x&y) with complemented edges.
+        let text = "\
+.ver DDDMP-2.0
+.mode A
+.varinfo 0
+.nnodes 3
+.nvars 2
+.nsuppvars 2
+.suppvarnames x y
+.orderedvarnames x y
+.ids 0 1
+.permids 0 1
+.nroots 1
+.rootids -3
+.nodes
+1 T 1 0 0
+2 1 1 1 -1
+3 0 0 2 -1
+.end
+";
+        let loaded = BinaryDecisionDiagram::from_dddmp(text).unwrap();
+        assert!(loaded.equivalent(&BinaryDecisionDiagram::from_str("!(x&y)")));
+        let reordered = text.replace(".orderedvarnames x y", ".orderedvarnames y x");
+        assert!(matches!(
+            BinaryDecisionDiagram::from_dddmp(&reordered),
+            Err(it) if it.contains("not ordered")
+        ));
+        let duplicated = text.replace(".orderedvarnames x y", ".orderedvarnames x x");
+        assert!(BinaryDecisionDiagram::from_dddmp(&duplicated).is_err());
+        assert!(BinaryDecisionDiagram::from_dddmp(".nodes\n.end\n").is_err());
+    }
+
+    #[test]
+    fn test_dddmp_parity() {
+        // Every path of the parity function is distinct, so this only finishes if shared nodes
+        // are exported once.
+        let parity = (0..32)
+            .map(|it| BinaryDecisionDiagram::variable(&format!("x{it}")))
+            .reduce(|lhs, rhs| lhs.xor(&rhs))
+            .unwrap();
+        let text = parity.to_dddmp();
+        // With complemented edges, one node per variable and the terminal are enough.
+        assert!(text.contains(".nnodes 33\n"));
+        let loaded = BinaryDecisionDiagram::from_dddmp(&text).unwrap();
+        assert!(loaded.equivalent(&parity));
+    }
+}
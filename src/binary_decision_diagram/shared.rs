@@ -0,0 +1,224 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use petgraph::{dot::Dot, stable_graph::NodeIndex, stable_graph::StableDiGraph};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use super::{BinaryDecisionDiagram, Manager};
+use crate::formula::{expression, Expression};
+
+// Several named functions, such as the outputs of a circuit, over one node store,
+// so the logic they have in common is only stored once.
+#[wasm_bindgen]
+pub struct SharedBinaryDecisionDiagram {
+    manager: Rc<RefCell<Manager>>,
+    // Kept in insertion order.
+    roots: Vec<(String, NodeIndex)>,
+}
+
+impl Default for SharedBinaryDecisionDiagram {
+    fn default() -> Self {
+        Self {
+            manager: Manager::shared(),
+            roots: Vec::new(),
+        }
+    }
+}
+
+impl Drop for SharedBinaryDecisionDiagram {
+    fn drop(&mut self) {
+        if let Ok(mut manager) = self.manager.try_borrow_mut() {
+            for (_, root) in &self.roots {
+                manager.dereference(*root);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl SharedBinaryDecisionDiagram {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds the output `name`, replacing any output of that name.
+    pub fn insert_str(&mut self, name: &str, code: &str) {
+        let expr = expression::parse(code).unwrap().1;
+        self.insert_formula(name, &expr);
+    }
+
+    #[wasm_bindgen(js_name = insert)]
+    pub fn insert_js(&mut self, name: &str, diagram: &BinaryDecisionDiagram) {
+        self.insert(name, diagram);
+    }
+
+    pub fn get(&self, name: &str) -> Option<BinaryDecisionDiagram> {
+        let (_, root) = self.roots.iter().find(|(it, _)| it == name)?;
+        Some(BinaryDecisionDiagram::from_root(
+            self.manager.clone(),
+            *root,
+        ))
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let Some(position) = self.roots.iter().position(|(it, _)| it == name) else {
+            return false;
+        };
+        let (_, root) = self.roots.remove(position);
+        self.manager.borrow_mut().dereference(root);
+        true
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.roots.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    // Nodes reachable from any output, counting shared nodes once.
+    pub fn node_count(&self) -> usize {
+        self.manager
+            .borrow()
+            .reachable_from(&self.root_nodes())
+            .len()
+    }
+
+    // Outputs share the manager's unique table, so they are already reduced together.
+    pub fn reduce(self) -> Self {
+        self
+    }
+
+    // Every output is drawn as a node labelled with its name, pointing at its root.
+    pub fn dot(&self) -> String {
+        let manager = self.manager.borrow();
+        let nodes = manager.reachable_from(&self.root_nodes());
+        let mut graph = StableDiGraph::new();
+        let outputs: Vec<_> = self
+            .roots
+            .iter()
+            .map(|(name, _)| graph.add_node(name.clone()))
+            .collect();
+        let node_map: HashMap<_, _> = nodes
+            .iter()
+            .map(|node| (*node, graph.add_node(manager.label(*node).to_string())))
+            .collect();
+        for (output, (_, root)) in outputs.into_iter().zip(&self.roots) {
+            graph.add_edge(output, node_map[root], String::new());
+        }
+        for node in nodes.iter().filter(|it| !manager.is_terminal(**it)) {
+            let (false_child, true_child) = manager.children(*node);
+            graph.add_edge(node_map[node], node_map[&false_child], false.to_string());
+            graph.add_edge(node_map[node], node_map[&true_child], true.to_string());
+        }
+        Dot::new(&graph).to_string()
+    }
+}
+
+impl SharedBinaryDecisionDiagram {
+    pub fn insert(&mut self, name: &str, diagram: &BinaryDecisionDiagram) {
+        let root = if Rc::ptr_eq(&self.manager, &diagram.manager) {
+            diagram.root
+        } else {
+            self.manager
+                .borrow_mut()
+                .import(&diagram.manager.borrow(), diagram.root)
+        };
+        self.set_root(name, root);
+    }
+
+    pub fn insert_formula(&mut self, name: &str, formula: &Expression) {
+        let diagram = BinaryDecisionDiagram::from_formula_in(&self.manager, formula);
+        self.set_root(name, diagram.root);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, BinaryDecisionDiagram)> + '_ {
+        self.roots.iter().map(|(name, root)| {
+            let diagram = BinaryDecisionDiagram::from_root(self.manager.clone(), *root);
+            (name.as_str(), diagram)
+        })
+    }
+
+    fn root_nodes(&self) -> Vec<NodeIndex> {
+        self.roots.iter().map(|(_, root)| *root).collect()
+    }
+
+    fn set_root(&mut self, name: &str, root: NodeIndex) {
+        let mut manager = self.manager.borrow_mut();
+        manager.reference(root);
+        match self.roots.iter_mut().find(|(it, _)| it == name) {
+            Some((_, old)) => {
+                manager.dereference(*old);
+                *old = root;
+            }
+            None => self.roots.push((name.to_string(), root)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_adder() -> SharedBinaryDecisionDiagram {
+        let mut shared = SharedBinaryDecisionDiagram::new();
+        shared.insert_str("sum", "a&!b&!c|!a&b&!c|!a&!b&c|a&b&c");
+        shared.insert_str("carry", "a&b|a&c|b&c");
+        shared
+    }
+
+    #[test]
+    fn test_sharing() {
+        let shared = full_adder();
+        assert_eq!(shared.names(), vec!["sum", "carry"]);
+        let sum = shared.get("sum").unwrap();
+        let carry = shared.get("carry").unwrap();
+        assert!(sum.equivalent(&BinaryDecisionDiagram::from_str(
+            "a&!b&!c|!a&b&!c|!a&!b&c|a&b&c"
+        )));
+        assert!(carry.equivalent(&BinaryDecisionDiagram::from_str("a&b|a&c|b&c")));
+        assert!(shared.node_count() < sum.node_count() + carry.node_count());
+        let shared = shared.reduce();
+        assert_eq!(shared.node_count(), 10);
+        assert!(shared.get("overflow").is_none());
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut shared = full_adder();
+        shared.insert("sum", &BinaryDecisionDiagram::from_str("x|y"));
+        assert_eq!(shared.names(), vec!["sum", "carry"]);
+        assert!(shared
+            .get("sum")
+            .unwrap()
+            .equivalent(&BinaryDecisionDiagram::from_str("x|y")));
+        let names: Vec<_> = shared.iter().map(|(name, _)| name.to_string()).collect();
+        assert_eq!(names, vec!["sum", "carry"]);
+        assert!(shared.remove("carry"));
+        assert!(!shared.remove("carry"));
+        assert_eq!(shared.names(), vec!["sum"]);
+        assert_eq!(shared.node_count(), 4);
+    }
+
+    #[test]
+    fn test_dot() {
+        let mut shared = SharedBinaryDecisionDiagram::new();
+        shared.insert_str("f", "a&b");
+        shared.insert_str("g", "b");
+        assert_eq!(
+            shared.dot(),
+            r#"digraph {
+    0 [ label = "f" ]
+    1 [ label = "g" ]
+    2 [ label = "a" ]
+    3 [ label = "false" ]
+    4 [ label = "b" ]
+    5 [ label = "true" ]
+    0 -> 2 [ label = "" ]
+    1 -> 4 [ label = "" ]
+    2 -> 3 [ label = "false" ]
+    2 -> 4 [ label = "true" ]
+    4 -> 3 [ label = "false" ]
+    4 -> 5 [ label = "true" ]
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,602 @@
+use std::collections::{BTreeSet, HashMap};
+
+use num_bigint::{BigUint, RandBigInt};
+use petgraph::stable_graph::NodeIndex;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
+
+use super::{assignment_from_js, assignment_to_js, BinaryDecisionDiagram};
+use crate::ContainVariable;
+
+// A copy of a diagram's nodes, so that enumerating solutions is not affected by
+// reordering or garbage collection in the manager.
+struct Snapshot {
+    variables: Vec<String>,
+    // Position in `variables` and children of every non-terminal node.
+    nodes: HashMap<NodeIndex, (usize, NodeIndex, NodeIndex)>,
+    root: NodeIndex,
+    true_node: NodeIndex,
+}
+
+impl Snapshot {
+    fn position(&self, node: NodeIndex) -> usize {
+        self.nodes
+            .get(&node)
+            .map_or(self.variables.len(), |(position, _, _)| *position)
+    }
+
+    // Children of `node` when choosing the variable at `position`, which it may skip.
+    fn children_at(&self, node: NodeIndex, position: usize) -> (NodeIndex, NodeIndex) {
+        match self.nodes.get(&node) {
+            Some((node_position, false_child, true_child)) if *node_position == position => {
+                (*false_child, *true_child)
+            }
+            _ => (node, node),
+        }
+    }
+
+    // Satisfying assignments of the variables from `node`'s position on.
+    fn count(&self, node: NodeIndex, counted: &mut HashMap<NodeIndex, BigUint>) -> BigUint {
+        let Some((position, false_child, true_child)) = self.nodes.get(&node) else {
+            return BigUint::from((node == self.true_node) as u8);
+        };
+        if let Some(result) = counted.get(&node) {
+            return result.clone();
+        }
+        let result = self.completions(*false_child, position + 1, counted)
+            + self.completions(*true_child, position + 1, counted);
+        counted.insert(node, result.clone());
+        result
+    }
+
+    // Satisfying assignments of the variables from `position` on, once the ones before it led to `node`.
+    fn completions(
+        &self,
+        node: NodeIndex,
+        position: usize,
+        counted: &mut HashMap<NodeIndex, BigUint>,
+    ) -> BigUint {
+        self.count(node, counted) << (self.position(node) - position)
+    }
+
+    // The satisfying assignment with `index` solutions before it in lexicographic order.
+    fn unrank(
+        &self,
+        mut index: BigUint,
+        counted: &mut HashMap<NodeIndex, BigUint>,
+    ) -> Option<Vec<bool>> {
+        if index >= self.completions(self.root, 0, counted) {
+            return None;
+        }
+        let mut node = self.root;
+        let mut result = Vec::with_capacity(self.variables.len());
+        for position in 0..self.variables.len() {
+            let (false_child, true_child) = self.children_at(node, position);
+            let before = self.completions(false_child, position + 1, counted);
+            let value = index >= before;
+            if value {
+                index -= before;
+                node = true_child;
+            } else {
+                node = false_child;
+            }
+            result.push(value);
+        }
+        Some(result)
+    }
+
+    // Visits every node once, `probabilities` holds the chance of each variable being true.
+    fn probability(&self, probabilities: &[f64]) -> f64 {
+        fn visit(
+            snapshot: &Snapshot,
+            probabilities: &[f64],
+            node: NodeIndex,
+            visited: &mut HashMap<NodeIndex, f64>,
+        ) -> f64 {
+            let Some((position, false_child, true_child)) = snapshot.nodes.get(&node) else {
+                return (node == snapshot.true_node) as u8 as f64;
+            };
+            if let Some(result) = visited.get(&node) {
+                return *result;
+            }
+            let probability = probabilities[*position];
+            let result = (1.0 - probability)
+                * visit(snapshot, probabilities, *false_child, visited)
+                + probability * visit(snapshot, probabilities, *true_child, visited);
+            visited.insert(node, result);
+            result
+        }
+        visit(self, probabilities, self.root, &mut HashMap::new())
+    }
+}
+
+impl BinaryDecisionDiagram {
+    // `extra` variables are added to the ones of the diagram, ordered like in the manager.
+    fn counted_variables(&self, extra: &[String]) -> Vec<String> {
+        let manager = self.manager.borrow();
+        let mut variables: BTreeSet<_> = self
+            .variables()
+            .into_iter()
+            .chain(extra.iter().cloned())
+            .chain(manager.support(self.root))
+            .filter_map(|it| Some((manager.order().level(&it)?, it)))
+            .collect();
+        // Variables never seen by the manager can not appear in the diagram.
+        let unknown = extra
+            .iter()
+            .filter(|it| manager.order().level(it).is_none())
+            .cloned();
+        variables.extend(unknown.map(|it| (usize::MAX, it)));
+        variables.into_iter().map(|(_, it)| it).collect()
+    }
+
+    fn snapshot(&self, extra: &[String]) -> Snapshot {
+        let variables = self.counted_variables(extra);
+        let positions: HashMap<_, _> = variables
+            .iter()
+            .enumerate()
+            .map(|(position, variable)| (variable.as_str(), position))
+            .collect();
+        let manager = self.manager.borrow();
+        let nodes = manager
+            .reachable(self.root)
+            .into_iter()
+            .filter(|it| !manager.is_terminal(*it))
+            .map(|it| {
+                let (false_child, true_child) = manager.children(it);
+                (it, (positions[manager.label(it)], false_child, true_child))
+            })
+            .collect();
+        Snapshot {
+            variables,
+            nodes,
+            root: self.root,
+            true_node: manager.constant(true),
+        }
+    }
+
+    // Number of satisfying assignments over `variables()`,
+    // including variables the function does not depend on.
+    pub fn sat_count(&self) -> BigUint {
+        self.sat_count_over(&[])
+    }
+
+    // Like `sat_count`, counting `extra` variables as well.
+    pub fn sat_count_over(&self, extra: &[String]) -> BigUint {
+        let snapshot = self.snapshot(extra);
+        snapshot.completions(snapshot.root, 0, &mut HashMap::new())
+    }
+
+    // Sum over the satisfying assignments of the product of their literal weights, which are given
+    // as `(weight when false, weight when true)` and must not be negative. Like `sat_count_over`,
+    // it ranges over `variables()` and the weighted variables, unweighted ones weigh 1 either way.
+    pub fn weighted_count(&self, weights: &HashMap<String, (f64, f64)>) -> Result<f64, String> {
+        let extra: Vec<_> = weights.keys().cloned().collect();
+        let snapshot = self.snapshot(&extra);
+        let mut scale = 1.0;
+        let mut probabilities = Vec::with_capacity(snapshot.variables.len());
+        for variable in &snapshot.variables {
+            let (false_weight, true_weight) = weights.get(variable).copied().unwrap_or((1.0, 1.0));
+            if !(false_weight >= 0.0 && true_weight >= 0.0) {
+                return Err(format!("weights of `{variable}` must not be negative"));
+            }
+            let total = false_weight + true_weight;
+            if total == 0.0 {
+                return Ok(0.0);
+            }
+            // Normalized, so that the weights of a variable skipped by an edge sum to 1.
+            scale *= total;
+            probabilities.push(true_weight / total);
+        }
+        Ok(scale * snapshot.probability(&probabilities))
+    }
+
+    // Probability of being true when every variable is independently true with the given
+    // probability, only the variables the diagram depends on need one.
+    pub fn probability(&self, probabilities: &HashMap<String, f64>) -> Result<f64, String> {
+        let snapshot = self.snapshot(&[]);
+        let support: BTreeSet<_> = snapshot.nodes.values().map(|(it, _, _)| *it).collect();
+        let mut values = Vec::with_capacity(snapshot.variables.len());
+        for (position, variable) in snapshot.variables.iter().enumerate() {
+            let value = match probabilities.get(variable) {
+                Some(value) if (0.0..=1.0).contains(value) => *value,
+                Some(_) => {
+                    return Err(format!(
+                        "probability of `{variable}` must be between 0 and 1"
+                    ))
+                }
+                None if support.contains(&position) => {
+                    return Err(format!("variable `{variable}` has no probability"))
+                }
+                None => 0.5,
+            };
+            values.push(value);
+        }
+        Ok(snapshot.probability(&values))
+    }
+
+    // Solutions are ordered lexicographically over `variables()` in the manager's order,
+    // with false before true, so `unrank(0)` is the smallest one.
+    pub fn unrank(&self, index: &BigUint) -> Option<HashMap<String, bool>> {
+        let snapshot = self.snapshot(&[]);
+        let values = snapshot.unrank(index.clone(), &mut HashMap::new())?;
+        Some(snapshot.variables.iter().cloned().zip(values).collect())
+    }
+
+    // The number of solutions before `assignment`, `None` unless it is a solution
+    // which assigns every variable of `variables()`.
+    pub fn rank(&self, assignment: &HashMap<String, bool>) -> Option<BigUint> {
+        let snapshot = self.snapshot(&[]);
+        let mut counted = HashMap::new();
+        let mut node = snapshot.root;
+        let mut result = BigUint::default();
+        for (position, variable) in snapshot.variables.iter().enumerate() {
+            let (false_child, true_child) = snapshot.children_at(node, position);
+            if *assignment.get(variable)? {
+                result += snapshot.completions(false_child, position + 1, &mut counted);
+                node = true_child;
+            } else {
+                node = false_child;
+            }
+        }
+        (node == snapshot.true_node).then_some(result)
+    }
+
+    // `count` solutions drawn independently and uniformly, the same `seed` gives the same ones.
+    pub fn sample(&self, count: usize, seed: u64) -> Vec<HashMap<String, bool>> {
+        self.sample_with(count, &mut Pcg64Mcg::seed_from_u64(seed))
+    }
+
+    pub fn sample_with<R: Rng + ?Sized>(
+        &self,
+        count: usize,
+        rng: &mut R,
+    ) -> Vec<HashMap<String, bool>> {
+        let snapshot = self.snapshot(&[]);
+        let mut counted = HashMap::new();
+        let total = snapshot.completions(snapshot.root, 0, &mut counted);
+        if total == BigUint::default() {
+            return Vec::new();
+        }
+        (0..count)
+            .map(|_| {
+                let values = snapshot
+                    .unrank(rng.gen_biguint_below(&total), &mut counted)
+                    .unwrap();
+                snapshot.variables.iter().cloned().zip(values).collect()
+            })
+            .collect()
+    }
+
+    // Satisfying cubes over `variables()`, `None` marks a variable whose value does not matter.
+    // There is one cube for each path to the true terminal, so cubes never overlap.
+    pub fn sat_cubes(&self) -> SatCubes {
+        let snapshot = self.snapshot(&[]);
+        let stack = vec![(snapshot.root, vec![None; snapshot.variables.len()])];
+        SatCubes { snapshot, stack }
+    }
+
+    // Every satisfying assignment over `variables()`, each exactly once.
+    pub fn sat_assignments(&self) -> SatAssignments {
+        SatAssignments {
+            cubes: self.sat_cubes(),
+            current: None,
+        }
+    }
+}
+
+pub struct SatCubes {
+    snapshot: Snapshot,
+    stack: Vec<(NodeIndex, Vec<Option<bool>>)>,
+}
+
+impl SatCubes {
+    fn next_cube(&mut self) -> Option<Vec<Option<bool>>> {
+        while let Some((node, cube)) = self.stack.pop() {
+            let Some((position, false_child, true_child)) = self.snapshot.nodes.get(&node) else {
+                if node == self.snapshot.true_node {
+                    return Some(cube);
+                }
+                continue;
+            };
+            let mut true_cube = cube.clone();
+            true_cube[*position] = Some(true);
+            self.stack.push((*true_child, true_cube));
+            let mut false_cube = cube;
+            false_cube[*position] = Some(false);
+            self.stack.push((*false_child, false_cube));
+        }
+        None
+    }
+}
+
+impl Iterator for SatCubes {
+    type Item = HashMap<String, Option<bool>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cube = self.next_cube()?;
+        Some(self.snapshot.variables.iter().cloned().zip(cube).collect())
+    }
+}
+
+pub struct SatAssignments {
+    cubes: SatCubes,
+    // The cube being expanded and the value of its don't-care positions so far.
+    current: Option<(Vec<Option<bool>>, Vec<bool>)>,
+}
+
+impl Iterator for SatAssignments {
+    type Item = HashMap<String, bool>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current.is_none() {
+            let cube = self.cubes.next_cube()?;
+            let free = cube.iter().filter(|it| it.is_none()).count();
+            self.current = Some((cube, vec![false; free]));
+        }
+        let (cube, free_values) = self.current.as_mut().unwrap();
+        let mut free_values_iter = free_values.iter();
+        let result = self
+            .cubes
+            .snapshot
+            .variables
+            .iter()
+            .cloned()
+            .zip(
+                cube.iter()
+                    .map(|it| it.unwrap_or_else(|| *free_values_iter.next().unwrap())),
+            )
+            .collect();
+        // Count up in binary, the last don't-care variable changes fastest.
+        match free_values.iter().rposition(|it| !*it) {
+            Some(position) => {
+                free_values[position] = true;
+                free_values[position + 1..].fill(false);
+            }
+            None => self.current = None,
+        }
+        Some(result)
+    }
+}
+
+#[wasm_bindgen]
+impl BinaryDecisionDiagram {
+    // In decimal, since the count easily exceeds the range of a JS number.
+    #[wasm_bindgen(js_name = sat_count)]
+    pub fn sat_count_js(&self) -> String {
+        self.sat_count().to_string()
+    }
+
+    #[wasm_bindgen(js_name = sample)]
+    pub fn sample_js(&self, count: usize, seed: u64) -> Vec<JsValue> {
+        self.sample(count, seed)
+            .iter()
+            .map(assignment_to_js)
+            .collect()
+    }
+
+    // `index` is in decimal like `sat_count`, `undefined` if there are not that many solutions.
+    #[wasm_bindgen(js_name = unrank)]
+    pub fn unrank_js(&self, index: &str) -> Result<JsValue, JsValue> {
+        let index = index
+            .parse()
+            .map_err(|_| JsValue::from_str("index must be a non-negative integer"))?;
+        Ok(self
+            .unrank(&index)
+            .map_or(JsValue::UNDEFINED, |it| assignment_to_js(&it)))
+    }
+
+    #[wasm_bindgen(js_name = rank)]
+    pub fn rank_js(&self, assignment: &JsValue) -> Result<Option<String>, JsValue> {
+        Ok(self
+            .rank(&assignment_from_js(assignment)?)
+            .map(|it| it.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = weighted_count)]
+    pub fn weighted_count_js(
+        &self,
+        variables: Vec<String>,
+        false_weights: Vec<f64>,
+        true_weights: Vec<f64>,
+    ) -> Result<f64, JsValue> {
+        if variables.len() != false_weights.len() || variables.len() != true_weights.len() {
+            return Err(JsValue::from_str(
+                "every variable needs exactly one weight for each value",
+            ));
+        }
+        let weights = variables
+            .into_iter()
+            .zip(false_weights.into_iter().zip(true_weights))
+            .collect();
+        self.weighted_count(&weights)
+            .map_err(|it| JsValue::from_str(&it))
+    }
+
+    #[wasm_bindgen(js_name = probability)]
+    pub fn probability_js(
+        &self,
+        variables: Vec<String>,
+        probabilities: Vec<f64>,
+    ) -> Result<f64, JsValue> {
+        if variables.len() != probabilities.len() {
+            return Err(JsValue::from_str(
+                "every variable needs exactly one probability",
+            ));
+        }
+        let probabilities = variables.into_iter().zip(probabilities).collect();
+        self.probability(&probabilities)
+            .map_err(|it| JsValue::from_str(&it))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::Evaluable;
+
+    #[test]
+    fn test_sat_count() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c");
+        assert_eq!(bdd.sat_count(), BigUint::from(5u8));
+        let bdd = BinaryDecisionDiagram::from_str("a|b&!b");
+        assert_eq!(bdd.sat_count(), BigUint::from(2u8));
+        assert_eq!(
+            bdd.sat_count_over(&["x".to_string(), "y".to_string()]),
+            BigUint::from(8u8)
+        );
+        let bdd = BinaryDecisionDiagram::from_str("a&!a");
+        assert_eq!(bdd.sat_count(), BigUint::default());
+        let bdd = BinaryDecisionDiagram::from_str("a|!a");
+        assert_eq!(bdd.sat_count(), BigUint::from(2u8));
+    }
+
+    #[test]
+    fn test_sat_count_many_variables() {
+        let variables: Vec<String> = ('a'..='z')
+            .flat_map(|x| ('a'..='e').map(move |y| format!("{x}{y}")))
+            .collect();
+        let bdd = BinaryDecisionDiagram::from_str(&variables.join("|"));
+        let expected = (BigUint::from(1u8) << variables.len()) - 1u8;
+        assert_eq!(bdd.sat_count(), expected);
+    }
+
+    #[test]
+    fn test_probability() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c|d&!d");
+        let probabilities = [("a", 0.5), ("b", 0.4), ("c", 0.1)]
+            .into_iter()
+            .map(|(variable, value)| (variable.to_string(), value))
+            .collect();
+        let expected = 1.0 - (1.0 - 0.5 * 0.4) * (1.0 - 0.1);
+        assert!((bdd.probability(&probabilities).unwrap() - expected).abs() < 1e-12);
+        let mut missing = probabilities.clone();
+        missing.remove("c");
+        assert_eq!(
+            bdd.probability(&missing),
+            Err("variable `c` has no probability".to_string())
+        );
+        missing.insert("c".to_string(), 1.5);
+        assert!(bdd.probability(&missing).is_err());
+        assert_eq!(
+            BinaryDecisionDiagram::from_str("a|!a").probability(&HashMap::new()),
+            Ok(1.0)
+        );
+    }
+
+    #[test]
+    fn test_weighted_count() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c");
+        assert_eq!(bdd.weighted_count(&HashMap::new()), Ok(5.0));
+        let weights: HashMap<_, _> = [("a", (1.0, 2.0)), ("c", (3.0, 0.5)), ("x", (1.0, 1.0))]
+            .into_iter()
+            .map(|(variable, value)| (variable.to_string(), value))
+            .collect();
+        // 0.5 + 0.5 + 1 + 6 + 1 for the satisfying assignments of a, b and c, twice for x.
+        assert!((bdd.weighted_count(&weights).unwrap() - 18.0).abs() < 1e-12);
+        let mut zero = weights.clone();
+        zero.insert("x".to_string(), (0.0, 0.0));
+        assert_eq!(bdd.weighted_count(&zero), Ok(0.0));
+        zero.insert("x".to_string(), (-1.0, 1.0));
+        assert!(bdd.weighted_count(&zero).is_err());
+    }
+
+    #[test]
+    fn test_rank_and_unrank() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c|d&!d");
+        let values = |index: u8| -> Vec<bool> {
+            let assignment = bdd.unrank(&BigUint::from(index)).unwrap();
+            ["a", "b", "c", "d"].map(|it| assignment[it]).to_vec()
+        };
+        assert_eq!(values(0), [false, false, true, false]);
+        assert_eq!(values(1), [false, false, true, true]);
+        assert_eq!(values(2), [false, true, true, false]);
+        assert_eq!(values(9), [true, true, true, true]);
+        assert_eq!(bdd.unrank(&BigUint::from(10u8)), None);
+        let mut previous = None;
+        for index in 0..10u8 {
+            let assignment = bdd.unrank(&BigUint::from(index)).unwrap();
+            assert!(bdd.eval(&assignment));
+            assert_eq!(bdd.rank(&assignment), Some(BigUint::from(index)));
+            let key: Vec<_> = ["a", "b", "c", "d"].map(|it| assignment[it]).to_vec();
+            assert!(previous < Some(key.clone()));
+            previous = Some(key);
+        }
+        let not_a_solution = [("a", false), ("b", false), ("c", false), ("d", false)]
+            .into_iter()
+            .map(|(variable, value)| (variable.to_string(), value))
+            .collect();
+        assert_eq!(bdd.rank(&not_a_solution), None);
+        assert_eq!(bdd.rank(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_sample() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c");
+        let samples = bdd.sample(1000, 42);
+        assert_eq!(samples.len(), 1000);
+        assert_eq!(samples, bdd.sample(1000, 42));
+        assert_ne!(samples, bdd.sample(1000, 43));
+        let mut frequencies = [0; 5];
+        for sample in &samples {
+            assert!(bdd.eval(sample));
+            let rank = bdd.rank(sample).unwrap();
+            frequencies[usize::try_from(&rank).unwrap()] += 1;
+        }
+        // Each of the 5 solutions is expected 200 times.
+        assert!(frequencies.iter().all(|it| (150..250).contains(it)));
+        assert!(BinaryDecisionDiagram::from_str("a&!a")
+            .sample(10, 42)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_sat_cubes() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c");
+        let cubes: Vec<_> = bdd.sat_cubes().collect();
+        let cube = |a, b, c| -> HashMap<String, Option<bool>> {
+            [("a", a), ("b", b), ("c", c)]
+                .into_iter()
+                .map(|(variable, value)| (variable.to_string(), value))
+                .collect()
+        };
+        assert_eq!(
+            cubes,
+            vec![
+                cube(Some(false), None, Some(true)),
+                cube(Some(true), Some(false), Some(true)),
+                cube(Some(true), Some(true), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sat_assignments() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c|d&!d");
+        let assignments: Vec<_> = bdd.sat_assignments().collect();
+        assert_eq!(assignments.len(), 10);
+        assert_eq!(BigUint::from(assignments.len()), bdd.sat_count());
+        for assignment in &assignments {
+            assert_eq!(assignment.len(), 4);
+            assert!(bdd.eval(assignment));
+        }
+        let distinct: BTreeSet<Vec<(&String, &bool)>> = assignments
+            .iter()
+            .map(|it| {
+                let mut entries: Vec<_> = it.iter().collect();
+                entries.sort();
+                entries
+            })
+            .collect();
+        assert_eq!(distinct.len(), 10);
+        assert_eq!(
+            BinaryDecisionDiagram::from_str("a&!a")
+                .sat_assignments()
+                .count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use petgraph::stable_graph::NodeIndex;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use super::{BinaryDecisionDiagram, Manager};
+use crate::formula::{not::NotOperand, And, Atom, Expression, Not, Or};
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpressionStrategy {
+    // One conjunction for every path to the true terminal.
+    SumOfPaths,
+    // `v&high|!v&low` for every node, simplified where a child is a terminal.
+    IfThenElse,
+    // An irredundant sum of products, with common literals factored out.
+    Factored,
+}
+
+// A conjunction of literals, each a variable level and its value.
+type Cube = Vec<(usize, bool)>;
+
+// A disjunction of cubes.
+type Cover = Vec<Cube>;
+
+fn literal(variable: &str, value: bool) -> Expression {
+    let atom = Atom::Variable(variable.to_string());
+    if value {
+        atom.into()
+    } else {
+        Not(NotOperand::Atom(atom)).into()
+    }
+}
+
+fn and(lhs: Expression, rhs: Expression) -> Expression {
+    And(lhs.into(), rhs.into()).into()
+}
+
+fn or(lhs: Expression, rhs: Expression) -> Expression {
+    Or(lhs.into(), rhs.into()).into()
+}
+
+fn disjunction(operands: impl IntoIterator<Item = Expression>) -> Expression {
+    operands
+        .into_iter()
+        .reduce(or)
+        .unwrap_or_else(|| Atom::Const(false).into())
+}
+
+fn conjunction(operands: impl IntoIterator<Item = Expression>) -> Expression {
+    operands
+        .into_iter()
+        .reduce(and)
+        .unwrap_or_else(|| Atom::Const(true).into())
+}
+
+fn paths(manager: &Manager, node: NodeIndex, path: &mut Cube, result: &mut Vec<Cube>) {
+    match manager.terminal_value(node) {
+        Some(true) => result.push(path.clone()),
+        Some(false) => {}
+        None => {
+            let (false_child, true_child) = manager.children(node);
+            for (child, value) in [(false_child, false), (true_child, true)] {
+                path.push((manager.level(node), value));
+                paths(manager, child, path, result);
+                path.pop();
+            }
+        }
+    }
+}
+
+fn if_then_else(
+    manager: &Manager,
+    node: NodeIndex,
+    converted: &mut HashMap<NodeIndex, Expression>,
+) -> Expression {
+    if let Some(value) = manager.terminal_value(node) {
+        return Atom::Const(value).into();
+    }
+    if let Some(result) = converted.get(&node) {
+        return result.clone();
+    }
+    let variable = manager.label(node);
+    let (false_child, true_child) = manager.children(node);
+    let low = if_then_else(manager, false_child, converted);
+    let high = if_then_else(manager, true_child, converted);
+    let result = match (low.constant_value(), high.constant_value()) {
+        (Some(false), Some(true)) => literal(variable, true),
+        (Some(true), Some(false)) => literal(variable, false),
+        (Some(false), _) => and(literal(variable, true), high),
+        (Some(true), _) => or(literal(variable, false), high),
+        (_, Some(false)) => and(literal(variable, false), low),
+        (_, Some(true)) => or(literal(variable, true), low),
+        _ => or(
+            and(literal(variable, true), high),
+            and(literal(variable, false), low),
+        ),
+    };
+    converted.insert(node, result.clone());
+    result
+}
+
+// Minato and Morreale's irredundant sum of products of any function between `lower` and `upper`,
+// returned together with the diagram of the cover.
+fn irredundant_cover(
+    manager: &mut Manager,
+    lower: NodeIndex,
+    upper: NodeIndex,
+    covered: &mut HashMap<(NodeIndex, NodeIndex), (NodeIndex, Cover)>,
+) -> (NodeIndex, Cover) {
+    if lower == manager.constant(false) {
+        return (lower, Vec::new());
+    }
+    if upper == manager.constant(true) {
+        return (upper, vec![Vec::new()]);
+    }
+    if let Some(result) = covered.get(&(lower, upper)) {
+        return result.clone();
+    }
+    let level = manager.level(lower).min(manager.level(upper));
+    let variable = manager.variable_at(level);
+    let (lower_false, lower_true) = cofactors(manager, lower, level);
+    let (upper_false, upper_true) = cofactors(manager, upper, level);
+    let only_false = manager.apply(|l, u| l && !u, lower_false, upper_true);
+    let (false_cover, false_cubes) = irredundant_cover(manager, only_false, upper_false, covered);
+    let only_true = manager.apply(|l, u| l && !u, lower_true, upper_false);
+    let (true_cover, true_cubes) = irredundant_cover(manager, only_true, upper_true, covered);
+    let rest_false = manager.apply(|l, c| l && !c, lower_false, false_cover);
+    let rest_true = manager.apply(|l, c| l && !c, lower_true, true_cover);
+    let rest_lower = manager.apply(|a, b| a || b, rest_false, rest_true);
+    let rest_upper = manager.apply(|a, b| a && b, upper_false, upper_true);
+    let (rest_cover, rest_cubes) = irredundant_cover(manager, rest_lower, rest_upper, covered);
+    let cover = manager.make_node(variable, false_cover, true_cover);
+    let cover = manager.apply(|a, b| a || b, cover, rest_cover);
+    let mut cubes = Vec::new();
+    for (side, value) in [(false_cubes, false), (true_cubes, true)] {
+        for mut cube in side {
+            cube.insert(0, (level, value));
+            cubes.push(cube);
+        }
+    }
+    cubes.extend(rest_cubes);
+    covered.insert((lower, upper), (cover, cubes.clone()));
+    (cover, cubes)
+}
+
+fn cofactors(manager: &Manager, node: NodeIndex, level: usize) -> (NodeIndex, NodeIndex) {
+    if manager.level(node) == level {
+        manager.children(node)
+    } else {
+        (node, node)
+    }
+}
+
+// Repeatedly pulls the literal shared by the most cubes out of them.
+fn factor(manager: &Manager, cubes: Cover) -> Expression {
+    let to_expression = |(level, value): (usize, bool)| {
+        literal(manager.variable_name(manager.variable_at(level)), value)
+    };
+    if cubes.len() <= 1 {
+        let cube = cubes.into_iter().next();
+        return match cube {
+            Some(cube) => conjunction(cube.into_iter().map(to_expression)),
+            None => Atom::Const(false).into(),
+        };
+    }
+    let mut counts: HashMap<(usize, bool), usize> = HashMap::new();
+    for literal in cubes.iter().flatten() {
+        *counts.entry(*literal).or_default() += 1;
+    }
+    let best = counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .max_by_key(|((level, value), count)| (*count, std::cmp::Reverse((*level, *value))));
+    let Some((best, _)) = best else {
+        return disjunction(cubes.into_iter().map(|cube| factor(manager, vec![cube])));
+    };
+    let (with, without): (Vec<_>, Vec<_>) =
+        cubes.into_iter().partition(|cube| cube.contains(&best));
+    let with: Vec<_> = with
+        .into_iter()
+        .map(|cube| cube.into_iter().filter(|it| *it != best).collect())
+        .collect();
+    let factored = if with.iter().any(|cube: &Cube| cube.is_empty()) {
+        to_expression(best)
+    } else {
+        and(to_expression(best), factor(manager, with))
+    };
+    if without.is_empty() {
+        factored
+    } else {
+        or(factored, factor(manager, without))
+    }
+}
+
+impl BinaryDecisionDiagram {
+    pub fn to_expression(&self, strategy: ExpressionStrategy) -> Expression {
+        let mut manager = self.manager.borrow_mut();
+        match strategy {
+            ExpressionStrategy::SumOfPaths => {
+                let mut cubes = Vec::new();
+                paths(&manager, self.root, &mut Vec::new(), &mut cubes);
+                disjunction(cubes.into_iter().map(|cube| {
+                    conjunction(cube.into_iter().map(|(level, value)| {
+                        literal(manager.variable_name(manager.variable_at(level)), value)
+                    }))
+                }))
+            }
+            ExpressionStrategy::IfThenElse => {
+                if_then_else(&manager, self.root, &mut HashMap::new())
+            }
+            ExpressionStrategy::Factored => {
+                let (_, cubes) =
+                    irredundant_cover(&mut manager, self.root, self.root, &mut HashMap::new());
+                factor(&manager, cubes)
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl BinaryDecisionDiagram {
+    // The formula as text, which `from_str` accepts again.
+    #[wasm_bindgen(js_name = to_expression)]
+    pub fn to_expression_js(&self, strategy: ExpressionStrategy) -> String {
+        self.to_expression(strategy).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formula::parse;
+
+    const STRATEGIES: [ExpressionStrategy; 3] = [
+        ExpressionStrategy::SumOfPaths,
+        ExpressionStrategy::IfThenElse,
+        ExpressionStrategy::Factored,
+    ];
+
+    #[test]
+    fn test_round_trip() {
+        for code in ["a&b|c", "!(a&b)|c&!d", "a&!b|!a&b", "a|!a", "a&!a", "x"] {
+            let bdd = BinaryDecisionDiagram::from_str(code);
+            for strategy in STRATEGIES {
+                let text = bdd.to_expression(strategy).to_string();
+                assert_eq!(parse(&text).unwrap().1.to_string(), text);
+                assert!(
+                    BinaryDecisionDiagram::from_str(&text).equivalent(&bdd),
+                    "{code} became {text} with {strategy:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_strategies() {
+        let bdd = BinaryDecisionDiagram::from_str("a&b|c");
+        assert_eq!(
+            bdd.to_expression(ExpressionStrategy::SumOfPaths)
+                .to_string(),
+            "!a&c|a&!b&c|a&b"
+        );
+        assert_eq!(
+            bdd.to_expression(ExpressionStrategy::IfThenElse)
+                .to_string(),
+            "a&(b|c)|!a&c"
+        );
+        assert_eq!(
+            bdd.to_expression(ExpressionStrategy::Factored).to_string(),
+            "a&b|c"
+        );
+        let bdd = BinaryDecisionDiagram::from_str("a&b|a&c|a&d");
+        assert_eq!(
+            bdd.to_expression(ExpressionStrategy::Factored).to_string(),
+            "a&(b|c|d)"
+        );
+        let quantified = BinaryDecisionDiagram::from_str("a&b|c").exists("a");
+        assert_eq!(
+            quantified
+                .to_expression(ExpressionStrategy::Factored)
+                .to_string(),
+            "b|c"
+        );
+    }
+
+    #[test]
+    fn test_factored_shared_subfunctions() {
+        // Every pair of adjacent variables shares cofactors, which the cover must only visit once.
+        let names: Vec<_> = ('a'..='y').collect();
+        let code = names
+            .windows(2)
+            .map(|it| format!("{}&{}", it[0], it[1]))
+            .collect::<Vec<_>>()
+            .join("|");
+        let bdd = BinaryDecisionDiagram::from_str(&code);
+        // One node for the first and last variable, two for the others, and both terminals.
+        assert_eq!(bdd.node_count(), 50);
+        let text = bdd.to_expression(ExpressionStrategy::Factored).to_string();
+        assert!(BinaryDecisionDiagram::from_str(&text).equivalent(&bdd));
+    }
+}
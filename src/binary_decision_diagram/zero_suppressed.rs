@@ -0,0 +1,696 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+};
+
+use num_bigint::BigUint;
+use petgraph::{dot::Dot, stable_graph::NodeIndex};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use super::manager::VariableId;
+use super::node_store::{Node, NodeStore};
+use super::{BinaryDecisionDiagram, Manager, VariableOrder};
+use crate::ContainVariable;
+
+thread_local! {
+    static DEFAULT_MANAGER: Rc<RefCell<ZeroSuppressedManager>> =
+        Rc::new(RefCell::new(ZeroSuppressedManager::new()));
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum CacheKey {
+    Union(NodeIndex, NodeIndex),
+    Intersection(NodeIndex, NodeIndex),
+    Difference(NodeIndex, NodeIndex),
+    Subset0(NodeIndex, VariableId),
+    Subset1(NodeIndex, VariableId),
+    Change(NodeIndex, VariableId),
+}
+
+// Owns the nodes of zero-suppressed diagrams, which drop a node whose true edge leads to the
+// empty family instead of a node with equal children.
+// Terminal `false` is the empty family, terminal `true` the family holding just the empty set.
+// These diagrams are never reordered, so a variable's id is also its level.
+struct ZeroSuppressedManager {
+    store: NodeStore<bool>,
+    order: VariableOrder,
+    computed_cache: HashMap<CacheKey, NodeIndex>,
+    empty: NodeIndex,
+    base: NodeIndex,
+}
+
+impl ZeroSuppressedManager {
+    fn new() -> Self {
+        let mut store = NodeStore::new();
+        // Both terminals stay alive, even when no diagram refers to them.
+        let empty = store.terminal(false);
+        let base = store.terminal(true);
+        store.reference(empty);
+        store.reference(base);
+        Self {
+            store,
+            order: VariableOrder::default(),
+            computed_cache: HashMap::new(),
+            empty,
+            base,
+        }
+    }
+
+    fn variable_id(&mut self, name: &str) -> VariableId {
+        self.order.push(name)
+    }
+
+    fn level(&self, node: NodeIndex) -> usize {
+        match self.store.node(node) {
+            Node::Terminal(_) => usize::MAX,
+            Node::Variable(id) => id,
+        }
+    }
+
+    fn label(&self, node: NodeIndex) -> &str {
+        match self.store.node(node) {
+            Node::Terminal(true) => "true",
+            Node::Terminal(false) => "false",
+            Node::Variable(id) => self.order.variable_at(id),
+        }
+    }
+
+    fn make_node(
+        &mut self,
+        variable: VariableId,
+        false_child: NodeIndex,
+        true_child: NodeIndex,
+    ) -> NodeIndex {
+        if true_child == self.empty {
+            return false_child;
+        }
+        debug_assert!(variable < self.level(false_child) && variable < self.level(true_child));
+        self.store.make_node(variable, false_child, true_child)
+    }
+
+    // Removes every node unreachable from a referenced root and returns how many were removed.
+    fn collect_garbage(&mut self) -> usize {
+        let result = self.store.collect_garbage();
+        self.computed_cache.clear();
+        result
+    }
+
+    // Called between operations, when every node in use is held by a referenced root.
+    fn collect_garbage_if_needed(&mut self) {
+        if self.store.needs_collection() {
+            self.collect_garbage();
+        }
+    }
+
+    // The family holding just `set`.
+    fn single(&mut self, set: &BTreeSet<String>) -> NodeIndex {
+        let mut variables: Vec<_> = set.iter().map(|it| self.variable_id(it)).collect();
+        variables.sort_unstable_by(|a, b| b.cmp(a));
+        variables.into_iter().fold(self.base, |node, variable| {
+            self.make_node(variable, self.empty, node)
+        })
+    }
+
+    // Children of `node` at `level`, a node below it does not contain the variable at all.
+    fn cofactors_at(&self, node: NodeIndex, level: usize) -> (NodeIndex, NodeIndex) {
+        if self.level(node) == level {
+            self.store.children(node)
+        } else {
+            (node, self.empty)
+        }
+    }
+
+    fn union(&mut self, lhs: NodeIndex, rhs: NodeIndex) -> NodeIndex {
+        if lhs == self.empty || lhs == rhs {
+            return rhs;
+        }
+        if rhs == self.empty {
+            return lhs;
+        }
+        let key = CacheKey::Union(lhs.min(rhs), lhs.max(rhs));
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let level = self.level(lhs).min(self.level(rhs));
+        let (lhs_false, lhs_true) = self.cofactors_at(lhs, level);
+        let (rhs_false, rhs_true) = self.cofactors_at(rhs, level);
+        let false_child = self.union(lhs_false, rhs_false);
+        let true_child = self.union(lhs_true, rhs_true);
+        let result = self.make_node(level, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    fn intersection(&mut self, lhs: NodeIndex, rhs: NodeIndex) -> NodeIndex {
+        if lhs == self.empty || rhs == self.empty {
+            return self.empty;
+        }
+        if lhs == rhs {
+            return lhs;
+        }
+        let key = CacheKey::Intersection(lhs.min(rhs), lhs.max(rhs));
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let level = self.level(lhs).min(self.level(rhs));
+        let (lhs_false, lhs_true) = self.cofactors_at(lhs, level);
+        let (rhs_false, rhs_true) = self.cofactors_at(rhs, level);
+        let false_child = self.intersection(lhs_false, rhs_false);
+        let true_child = self.intersection(lhs_true, rhs_true);
+        let result = self.make_node(level, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    fn difference(&mut self, lhs: NodeIndex, rhs: NodeIndex) -> NodeIndex {
+        if lhs == self.empty || lhs == rhs {
+            return self.empty;
+        }
+        if rhs == self.empty {
+            return lhs;
+        }
+        let key = CacheKey::Difference(lhs, rhs);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let level = self.level(lhs).min(self.level(rhs));
+        let (lhs_false, lhs_true) = self.cofactors_at(lhs, level);
+        let (rhs_false, rhs_true) = self.cofactors_at(rhs, level);
+        let false_child = self.difference(lhs_false, rhs_false);
+        let true_child = self.difference(lhs_true, rhs_true);
+        let result = self.make_node(level, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    // The sets without `variable`.
+    fn subset0(&mut self, node: NodeIndex, variable: VariableId) -> NodeIndex {
+        let level = self.level(node);
+        if level > variable {
+            return node;
+        }
+        if level == variable {
+            return self.store.children(node).0;
+        }
+        let key = CacheKey::Subset0(node, variable);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let (false_child, true_child) = self.store.children(node);
+        let false_child = self.subset0(false_child, variable);
+        let true_child = self.subset0(true_child, variable);
+        let result = self.make_node(level, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    // The sets with `variable`, which is removed from them.
+    fn subset1(&mut self, node: NodeIndex, variable: VariableId) -> NodeIndex {
+        let level = self.level(node);
+        if level > variable {
+            return self.empty;
+        }
+        if level == variable {
+            return self.store.children(node).1;
+        }
+        let key = CacheKey::Subset1(node, variable);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let (false_child, true_child) = self.store.children(node);
+        let false_child = self.subset1(false_child, variable);
+        let true_child = self.subset1(true_child, variable);
+        let result = self.make_node(level, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    // Adds `variable` to the sets without it and removes it from the others.
+    fn change(&mut self, node: NodeIndex, variable: VariableId) -> NodeIndex {
+        let level = self.level(node);
+        if level > variable {
+            return self.make_node(variable, self.empty, node);
+        }
+        if level == variable {
+            let (false_child, true_child) = self.store.children(node);
+            return self.make_node(variable, true_child, false_child);
+        }
+        let key = CacheKey::Change(node, variable);
+        if let Some(result) = self.computed_cache.get(&key) {
+            return *result;
+        }
+        let (false_child, true_child) = self.store.children(node);
+        let false_child = self.change(false_child, variable);
+        let true_child = self.change(true_child, variable);
+        let result = self.make_node(level, false_child, true_child);
+        self.computed_cache.insert(key, result);
+        result
+    }
+
+    fn count(&self, node: NodeIndex, counted: &mut HashMap<NodeIndex, BigUint>) -> BigUint {
+        if self.store.is_terminal(node) {
+            return BigUint::from((node == self.base) as u8);
+        }
+        if let Some(result) = counted.get(&node) {
+            return result.clone();
+        }
+        let (false_child, true_child) = self.store.children(node);
+        let result = self.count(false_child, counted) + self.count(true_child, counted);
+        counted.insert(node, result.clone());
+        result
+    }
+}
+
+// A family of sets of variables, such as cut sets or covers.
+// Nodes are shared by every diagram of the thread.
+#[wasm_bindgen]
+pub struct ZeroSuppressedDecisionDiagram {
+    manager: Rc<RefCell<ZeroSuppressedManager>>,
+    root: NodeIndex,
+    // Variables the sets are drawn from, a boolean function of the family is over these.
+    variables: BTreeSet<String>,
+}
+
+impl Clone for ZeroSuppressedDecisionDiagram {
+    fn clone(&self) -> Self {
+        self.manager.borrow_mut().store.reference(self.root);
+        Self {
+            manager: self.manager.clone(),
+            root: self.root,
+            variables: self.variables.clone(),
+        }
+    }
+}
+
+impl Drop for ZeroSuppressedDecisionDiagram {
+    fn drop(&mut self) {
+        if let Ok(mut manager) = self.manager.try_borrow_mut() {
+            manager.store.dereference(self.root);
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl ZeroSuppressedDecisionDiagram {
+    // The family without any set.
+    pub fn empty() -> Self {
+        let manager = DEFAULT_MANAGER.with(|it| it.clone());
+        let root = manager.borrow().empty;
+        Self::from_root(manager, root, BTreeSet::new())
+    }
+
+    // The family holding just the empty set.
+    pub fn base() -> Self {
+        let manager = DEFAULT_MANAGER.with(|it| it.clone());
+        let root = manager.borrow().base;
+        Self::from_root(manager, root, BTreeSet::new())
+    }
+
+    pub fn single(set: Vec<String>) -> Self {
+        let manager = DEFAULT_MANAGER.with(|it| it.clone());
+        let variables = set.into_iter().collect();
+        let root = manager.borrow_mut().single(&variables);
+        Self::from_root(manager, root, variables)
+    }
+
+    pub fn union(&self, other: &ZeroSuppressedDecisionDiagram) -> Self {
+        let root = self.manager.borrow_mut().union(self.root, other.root);
+        self.combined_with(other, root)
+    }
+
+    pub fn intersection(&self, other: &ZeroSuppressedDecisionDiagram) -> Self {
+        let root = self
+            .manager
+            .borrow_mut()
+            .intersection(self.root, other.root);
+        self.combined_with(other, root)
+    }
+
+    pub fn difference(&self, other: &ZeroSuppressedDecisionDiagram) -> Self {
+        let root = self.manager.borrow_mut().difference(self.root, other.root);
+        self.combined_with(other, root)
+    }
+
+    pub fn change(&self, variable_name: &str) -> Self {
+        let mut manager = self.manager.borrow_mut();
+        let variable = manager.variable_id(variable_name);
+        let root = manager.change(self.root, variable);
+        drop(manager);
+        let mut variables = self.variables.clone();
+        variables.insert(variable_name.to_string());
+        self.with_root(root, variables)
+    }
+
+    pub fn subset0(&self, variable_name: &str) -> Self {
+        let mut manager = self.manager.borrow_mut();
+        let variable = manager.variable_id(variable_name);
+        let root = manager.subset0(self.root, variable);
+        drop(manager);
+        self.with_root(root, self.variables.clone())
+    }
+
+    pub fn subset1(&self, variable_name: &str) -> Self {
+        let mut manager = self.manager.borrow_mut();
+        let variable = manager.variable_id(variable_name);
+        let root = manager.subset1(self.root, variable);
+        drop(manager);
+        self.with_root(root, self.variables.clone())
+    }
+
+    // In decimal, since the count easily exceeds the range of a JS number.
+    #[wasm_bindgen(js_name = count)]
+    pub fn count_js(&self) -> String {
+        self.count().to_string()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.manager
+            .borrow()
+            .store
+            .reachable_from(&[self.root])
+            .len()
+    }
+
+    pub fn equivalent(&self, other: &ZeroSuppressedDecisionDiagram) -> bool {
+        self.root == other.root
+    }
+
+    // The family of the sets of variables which are true in the satisfying assignments.
+    pub fn from_binary_decision_diagram(diagram: &BinaryDecisionDiagram) -> Self {
+        fn convert(
+            manager: &mut ZeroSuppressedManager,
+            source: &Manager,
+            domain: &[(usize, VariableId)],
+            node: NodeIndex,
+            converted: &mut HashMap<(NodeIndex, usize), NodeIndex>,
+        ) -> NodeIndex {
+            let Some((level, variable)) = domain.first() else {
+                return if source.terminal_value(node) == Some(true) {
+                    manager.base
+                } else {
+                    manager.empty
+                };
+            };
+            if let Some(result) = converted.get(&(node, domain.len())) {
+                return *result;
+            }
+            let (false_child, true_child) = if source.level(node) == *level {
+                source.children(node)
+            } else {
+                (node, node)
+            };
+            let without = convert(manager, source, &domain[1..], false_child, converted);
+            let with = convert(manager, source, &domain[1..], true_child, converted);
+            // `change` keeps working if this manager orders the variables differently.
+            let with = manager.change(with, *variable);
+            let result = manager.union(without, with);
+            converted.insert((node, domain.len()), result);
+            result
+        }
+        let variables = diagram.variables();
+        let source = diagram.manager.borrow();
+        let manager = DEFAULT_MANAGER.with(|it| it.clone());
+        let mut manager_mut = manager.borrow_mut();
+        let mut domain: Vec<_> = variables
+            .iter()
+            .map(|it| {
+                (
+                    source.order().level(it).unwrap(),
+                    manager_mut.variable_id(it),
+                )
+            })
+            .collect();
+        domain.sort_unstable();
+        let root = convert(
+            &mut manager_mut,
+            &source,
+            &domain,
+            diagram.root,
+            &mut HashMap::new(),
+        );
+        drop(manager_mut);
+        Self::from_root(manager, root, variables)
+    }
+
+    // The function which holds exactly on the members of this family,
+    // over the variables the sets are drawn from.
+    pub fn to_binary_decision_diagram(&self) -> BinaryDecisionDiagram {
+        fn convert(
+            manager: &mut Manager,
+            source: &ZeroSuppressedManager,
+            node: NodeIndex,
+            base: NodeIndex,
+            converted: &mut HashMap<NodeIndex, NodeIndex>,
+        ) -> NodeIndex {
+            if node == source.empty {
+                return manager.constant(false);
+            }
+            if node == source.base {
+                return base;
+            }
+            if let Some(result) = converted.get(&node) {
+                return *result;
+            }
+            let variable = source.label(node);
+            let (false_child, true_child) = source.store.children(node);
+            let without = convert(manager, source, false_child, base, converted);
+            let with = convert(manager, source, true_child, base, converted);
+            // `base` made the variable false in the sets of the true child, flip it back.
+            let with = manager.restrict(with, variable, false);
+            let literal = manager.variable(variable);
+            let with = manager.apply(|x, f| x && f, literal, with);
+            let result = manager.apply(|f, t| f || t, without, with);
+            converted.insert(node, result);
+            result
+        }
+        let source = self.manager.borrow();
+        let manager = Manager::shared();
+        let mut manager_mut = manager.borrow_mut();
+        let mut variables: Vec<_> = self.variables.iter().collect();
+        variables.sort_unstable_by_key(|it| source.order.level(it));
+        manager_mut.add_variables(&variables);
+        // Every variable is false in the empty set.
+        let mut base = manager_mut.constant(true);
+        for variable in variables {
+            let literal = manager_mut.variable(variable);
+            base = manager_mut.apply(|x, b| !x && b, literal, base);
+        }
+        let root = convert(
+            &mut manager_mut,
+            &source,
+            self.root,
+            base,
+            &mut HashMap::new(),
+        );
+        drop(manager_mut);
+        let result = BinaryDecisionDiagram::from_root(manager, root);
+        result.variables_cache.set(self.variables.clone()).unwrap();
+        result
+    }
+
+    pub fn dot(&self) -> String {
+        let manager = self.manager.borrow();
+        let graph = manager
+            .store
+            .subgraph(self.root, |node| manager.label(node).to_string());
+        Dot::new(&graph).to_string()
+    }
+}
+
+impl ZeroSuppressedDecisionDiagram {
+    pub fn count(&self) -> BigUint {
+        self.manager.borrow().count(self.root, &mut HashMap::new())
+    }
+
+    // Every set of the family, each exactly once.
+    pub fn sets(&self) -> Vec<BTreeSet<String>> {
+        fn collect(
+            manager: &ZeroSuppressedManager,
+            node: NodeIndex,
+            set: &mut Vec<String>,
+            result: &mut Vec<BTreeSet<String>>,
+        ) {
+            if node == manager.base {
+                result.push(set.iter().cloned().collect());
+            }
+            if manager.store.is_terminal(node) {
+                return;
+            }
+            let (false_child, true_child) = manager.store.children(node);
+            collect(manager, false_child, set, result);
+            set.push(manager.label(node).to_string());
+            collect(manager, true_child, set, result);
+            set.pop();
+        }
+        let mut result = Vec::new();
+        collect(
+            &self.manager.borrow(),
+            self.root,
+            &mut Vec::new(),
+            &mut result,
+        );
+        result
+    }
+
+    fn from_root(
+        manager: Rc<RefCell<ZeroSuppressedManager>>,
+        root: NodeIndex,
+        variables: BTreeSet<String>,
+    ) -> Self {
+        {
+            let mut manager = manager.borrow_mut();
+            manager.store.reference(root);
+            manager.collect_garbage_if_needed();
+        }
+        Self {
+            manager,
+            root,
+            variables,
+        }
+    }
+
+    fn with_root(&self, root: NodeIndex, variables: BTreeSet<String>) -> Self {
+        Self::from_root(self.manager.clone(), root, variables)
+    }
+
+    fn combined_with(&self, other: &Self, root: NodeIndex) -> Self {
+        let variables = self.variables.union(&other.variables).cloned().collect();
+        self.with_root(root, variables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn family(sets: &[&[&str]]) -> ZeroSuppressedDecisionDiagram {
+        sets.iter()
+            .map(|set| {
+                ZeroSuppressedDecisionDiagram::single(set.iter().map(|it| it.to_string()).collect())
+            })
+            .fold(ZeroSuppressedDecisionDiagram::empty(), |result, set| {
+                result.union(&set)
+            })
+    }
+
+    fn sets(sets: &[&[&str]]) -> BTreeSet<BTreeSet<String>> {
+        sets.iter()
+            .map(|set| set.iter().map(|it| it.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let lhs = family(&[&["a", "b"], &["b"], &[]]);
+        let rhs = family(&[&["b"], &["c"]]);
+        assert_eq!(lhs.count(), BigUint::from(3u8));
+        let union: BTreeSet<_> = lhs.union(&rhs).sets().into_iter().collect();
+        assert_eq!(union, sets(&[&["a", "b"], &["b"], &[], &["c"]]));
+        let intersection: BTreeSet<_> = lhs.intersection(&rhs).sets().into_iter().collect();
+        assert_eq!(intersection, sets(&[&["b"]]));
+        let difference: BTreeSet<_> = lhs.difference(&rhs).sets().into_iter().collect();
+        assert_eq!(difference, sets(&[&["a", "b"], &[]]));
+        assert!(lhs
+            .difference(&lhs)
+            .equivalent(&ZeroSuppressedDecisionDiagram::empty()));
+        assert!(lhs.union(&rhs).equivalent(&rhs.union(&lhs)));
+    }
+
+    #[test]
+    fn test_subsets_and_change() {
+        let family = family(&[&["a", "b"], &["b", "c"], &["c"]]);
+        let without_b: BTreeSet<_> = family.subset0("b").sets().into_iter().collect();
+        assert_eq!(without_b, sets(&[&["c"]]));
+        let with_b: BTreeSet<_> = family.subset1("b").sets().into_iter().collect();
+        assert_eq!(with_b, sets(&[&["a"], &["c"]]));
+        let changed: BTreeSet<_> = family.change("b").sets().into_iter().collect();
+        assert_eq!(changed, sets(&[&["a"], &["c"], &["b", "c"]]));
+        let added: BTreeSet<_> = family.change("d").sets().into_iter().collect();
+        assert_eq!(
+            added,
+            sets(&[&["a", "b", "d"], &["b", "c", "d"], &["c", "d"]])
+        );
+        assert!(family
+            .subset1("x")
+            .equivalent(&ZeroSuppressedDecisionDiagram::empty()));
+        assert!(family.subset0("x").equivalent(&family));
+    }
+
+    #[test]
+    fn test_zero_suppression() {
+        // Every variable missing from a set costs no node, unlike in a diagram of the function.
+        let variables: Vec<_> = (0..20).map(|it| format!("x{it}")).collect();
+        let family = variables
+            .iter()
+            .map(|it| ZeroSuppressedDecisionDiagram::single(vec![it.clone()]))
+            .fold(ZeroSuppressedDecisionDiagram::empty(), |result, set| {
+                result.union(&set)
+            });
+        assert_eq!(family.count(), BigUint::from(20u8));
+        assert_eq!(family.node_count(), 22);
+        let function = family.to_binary_decision_diagram();
+        assert_eq!(function.sat_count(), BigUint::from(20u8));
+        assert!(function.node_count() > family.node_count());
+    }
+
+    #[test]
+    fn test_conversion() {
+        for code in ["a&b|c", "a|!a", "a&!a", "!a&!b", "a&!b|!a&b"] {
+            let bdd = BinaryDecisionDiagram::from_str(code);
+            let zdd = ZeroSuppressedDecisionDiagram::from_binary_decision_diagram(&bdd);
+            assert_eq!(zdd.count(), bdd.sat_count());
+            for set in zdd.sets() {
+                let assignment = bdd
+                    .variables()
+                    .into_iter()
+                    .map(|it| (it.clone(), set.contains(&it)))
+                    .collect();
+                assert!(bdd.try_eval(&assignment).unwrap());
+            }
+            let back = zdd.to_binary_decision_diagram();
+            assert!(back.equivalent(&bdd));
+            assert_eq!(back.variables(), bdd.variables());
+        }
+        let zdd = family(&[&["a", "b"], &["c"]]);
+        let bdd = zdd.to_binary_decision_diagram();
+        assert!(bdd.equivalent(&BinaryDecisionDiagram::from_str("a&b&!c|!a&!b&c")));
+        assert!(ZeroSuppressedDecisionDiagram::from_binary_decision_diagram(&bdd).equivalent(&zdd));
+    }
+
+    #[test]
+    fn test_garbage_collection() {
+        let kept = family(&[&["a", "b"], &["c"]]);
+        let manager = kept.manager.clone();
+        let power_set = (0..12)
+            .map(|it| format!("x{it}"))
+            .fold(ZeroSuppressedDecisionDiagram::base(), |result, variable| {
+                result.union(&result.change(&variable))
+            });
+        assert_eq!(power_set.count(), BigUint::from(1u16 << 12));
+        drop(power_set);
+        assert!(manager.borrow_mut().collect_garbage() > 0);
+        assert_eq!(manager.borrow().store.node_count(), kept.node_count());
+        let sets: BTreeSet<_> = kept.sets().into_iter().collect();
+        assert_eq!(sets, self::sets(&[&["a", "b"], &["c"]]));
+    }
+
+    #[test]
+    fn test_dot() {
+        let zdd = family(&[&["a", "b"], &["b"]]);
+        assert_eq!(
+            zdd.dot(),
+            r#"digraph {
+    0 [ label = "a" ]
+    1 [ label = "b" ]
+    2 [ label = "false" ]
+    3 [ label = "true" ]
+    0 -> 1 [ label = "false" ]
+    0 -> 1 [ label = "true" ]
+    1 -> 2 [ label = "false" ]
+    1 -> 3 [ label = "true" ]
+}
+"#
+        );
+    }
+}
@@ -6,15 +6,24 @@ use nom::multi::fold_many0;
 use nom::sequence::preceded;
 use nom::IResult;
 use std::collections::{BTreeSet, HashMap};
-use std::ops;
+use std::{fmt, ops};
 
 use super::{atom, in_brackets, not};
+use crate::binary_decision_diagram::Manager;
 use crate::formula::atom::Atom;
 use crate::formula::in_brackets::InBrackets;
 use crate::formula::not::Not;
-use crate::{ContainVariable, Evaluable};
+use crate::formula::Expression;
+use crate::{Assign, ContainVariable, Evaluable, SyntaxTree, ToBinaryDecisionDiagram};
+use petgraph::stable_graph::NodeIndex;
 
-#[enum_dispatch(Evaluable, ContainVariable)]
+#[enum_dispatch(
+    Evaluable,
+    ContainVariable,
+    ToBinaryDecisionDiagram,
+    SyntaxTree,
+    Assign
+)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum AndOperand {
     Atom,
@@ -42,9 +51,39 @@ fn parse_higher_priority_operand(code: &str) -> IResult<&str, AndOperand> {
     ))(code)
 }
 
+impl From<Expression> for AndOperand {
+    fn from(expression: Expression) -> Self {
+        match expression {
+            Expression::Atom(x) => AndOperand::Atom(x),
+            Expression::InBrackets(x) => AndOperand::InBrackets(x),
+            Expression::Not(x) => AndOperand::Not(x),
+            Expression::And(x) => AndOperand::And(Box::new(x)),
+            x => AndOperand::InBrackets(InBrackets(Box::new(x))),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct And(pub(crate) AndOperand, pub(crate) AndOperand);
 
+impl fmt::Display for AndOperand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AndOperand::Atom(x) => x.fmt(f),
+            AndOperand::InBrackets(x) => x.fmt(f),
+            AndOperand::Not(x) => x.fmt(f),
+            AndOperand::And(x) => x.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for And {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let And(lhs, rhs) = self;
+        write!(f, "{lhs}&{rhs}")
+    }
+}
+
 impl Evaluable for And {
     fn eval(&self, ctx: &HashMap<String, bool>) -> bool {
         let And(lhs, rhs) = self;
@@ -59,6 +98,36 @@ impl ContainVariable for And {
     }
 }
 
+impl ToBinaryDecisionDiagram for And {
+    fn to_binary_decision_diagram(&self, manager: &mut Manager) -> NodeIndex {
+        let And(lhs, rhs) = self;
+        let lhs = lhs.to_binary_decision_diagram(manager);
+        let rhs = rhs.to_binary_decision_diagram(manager);
+        manager.apply(|lhs, rhs| lhs && rhs, lhs, rhs)
+    }
+}
+
+impl Assign for And {
+    fn assign(&self, ctx: &HashMap<String, bool>) -> Expression {
+        let And(lhs, rhs) = self;
+        let lhs = lhs.assign(ctx);
+        let rhs = rhs.assign(ctx);
+        match (lhs.constant_value(), rhs.constant_value()) {
+            (Some(false), _) | (_, Some(false)) => Atom::Const(false).into(),
+            (Some(true), _) => rhs,
+            (_, Some(true)) => lhs,
+            _ => And(lhs.into(), rhs.into()).into(),
+        }
+    }
+}
+
+impl SyntaxTree for And {
+    fn operands(&self) -> Vec<&dyn SyntaxTree> {
+        let And(lhs, rhs) = self;
+        vec![lhs, rhs]
+    }
+}
+
 pub fn parse(code: &str) -> IResult<&str, And> {
     let (rest, first) = parse_higher_priority_operand(code)?;
     let (rest, second) = preceded(tag("&"), parse_higher_priority_operand)(rest)?;
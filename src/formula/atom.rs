@@ -2,8 +2,12 @@ use nom::{
     branch::alt, bytes::complete::tag, character::complete::alpha1, combinator::map, IResult,
 };
 
-use crate::{ContainVariable, Evaluable};
+use super::Expression;
+use crate::binary_decision_diagram::Manager;
+use crate::{Assign, ContainVariable, Evaluable, SyntaxTree, ToBinaryDecisionDiagram};
+use petgraph::stable_graph::NodeIndex;
 use std::collections::{BTreeSet, HashMap};
+use std::fmt;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Atom {
@@ -11,6 +15,15 @@ pub enum Atom {
     Const(bool),
 }
 
+impl fmt::Display for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Atom::Variable(x) => write!(f, "{x}"),
+            Atom::Const(c) => write!(f, "{}", *c as u8),
+        }
+    }
+}
+
 impl Evaluable for Atom {
     fn eval(&self, ctx: &HashMap<String, bool>) -> bool {
         match self {
@@ -29,6 +42,37 @@ impl ContainVariable for Atom {
     }
 }
 
+impl ToBinaryDecisionDiagram for Atom {
+    fn to_binary_decision_diagram(&self, manager: &mut Manager) -> NodeIndex {
+        match self {
+            Atom::Variable(x) => manager.variable(x),
+            Atom::Const(c) => manager.constant(*c),
+        }
+    }
+}
+
+impl Assign for Atom {
+    fn assign(&self, ctx: &HashMap<String, bool>) -> Expression {
+        match self {
+            Atom::Variable(x) if ctx.contains_key(x) => Atom::Const(ctx[x]).into(),
+            _ => self.clone().into(),
+        }
+    }
+}
+
+impl SyntaxTree for Atom {
+    fn operands(&self) -> Vec<&dyn SyntaxTree> {
+        Vec::new()
+    }
+
+    fn variable_name(&self) -> Option<&str> {
+        match self {
+            Atom::Variable(x) => Some(x),
+            Atom::Const(_) => None,
+        }
+    }
+}
+
 pub fn parse(code: &str) -> IResult<&str, Atom> {
     alt((
         map(tag("0"), |_| Atom::Const(false)),
@@ -1,14 +1,25 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
 use super::and::{self, And};
 use super::atom::{self, Atom};
 use super::in_brackets::{self, InBrackets};
 use super::not::{self, Not};
 use super::or::{self, Or};
+use crate::binary_decision_diagram::BinaryDecisionDiagram;
+use crate::{Assign, ContainVariable};
 use enum_dispatch::enum_dispatch;
 use nom::branch::alt;
 use nom::combinator::map;
 use nom::IResult;
 
-#[enum_dispatch(Evaluable, ContainVariable)]
+#[enum_dispatch(
+    Evaluable,
+    ContainVariable,
+    ToBinaryDecisionDiagram,
+    SyntaxTree,
+    Assign
+)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Expression {
     Atom,
@@ -18,6 +29,94 @@ pub enum Expression {
     Or,
 }
 
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Atom(x) => x.fmt(f),
+            Expression::InBrackets(x) => x.fmt(f),
+            Expression::Not(x) => x.fmt(f),
+            Expression::And(x) => x.fmt(f),
+            Expression::Or(x) => x.fmt(f),
+        }
+    }
+}
+
+impl Expression {
+    pub(crate) fn constant_value(&self) -> Option<bool> {
+        match self {
+            Expression::Atom(Atom::Const(c)) => Some(*c),
+            _ => None,
+        }
+    }
+
+    // Variables the formula actually depends on, unlike `variables`.
+    pub fn support(&self) -> BTreeSet<String> {
+        BinaryDecisionDiagram::from_formula(self)
+            .support()
+            .into_iter()
+            .collect()
+    }
+
+    // An equivalent formula without the variables it does not depend on.
+    pub fn remove_irrelevant_variables(&self) -> Expression {
+        let support = self.support();
+        let irrelevant = self
+            .variables()
+            .into_iter()
+            .filter(|it| !support.contains(it))
+            .map(|it| (it, false))
+            .collect();
+        self.assign(&irrelevant)
+    }
+
+    pub fn is_satisfiable(&self) -> bool {
+        BinaryDecisionDiagram::from_formula(self).is_satisfiable()
+    }
+
+    pub fn is_tautology(&self) -> bool {
+        BinaryDecisionDiagram::from_formula(self).is_tautology()
+    }
+
+    pub fn is_contradiction(&self) -> bool {
+        BinaryDecisionDiagram::from_formula(self).is_contradiction()
+    }
+
+    // Variables not needed to satisfy the formula are assigned false.
+    pub fn any_sat(&self) -> Option<HashMap<String, bool>> {
+        let mut result = BinaryDecisionDiagram::from_formula(self).any_sat()?;
+        for variable in self.variables() {
+            result.entry(variable).or_insert(false);
+        }
+        Some(result)
+    }
+
+    // See `BinaryDecisionDiagram::probability`.
+    pub fn probability(&self, probabilities: &HashMap<String, f64>) -> Result<f64, String> {
+        BinaryDecisionDiagram::from_formula(self).probability(probabilities)
+    }
+
+    // See `BinaryDecisionDiagram::weighted_count`.
+    pub fn weighted_count(&self, weights: &HashMap<String, (f64, f64)>) -> Result<f64, String> {
+        BinaryDecisionDiagram::from_formula(self).weighted_count(weights)
+    }
+
+    pub fn equivalent(&self, other: &Expression) -> bool {
+        BinaryDecisionDiagram::from_formula(self)
+            .equivalent(&BinaryDecisionDiagram::from_formula(other))
+    }
+
+    // Assigns every variable of both formulas, on which the two formulas evaluate differently.
+    pub fn counterexample(&self, other: &Expression) -> Option<HashMap<String, bool>> {
+        let lhs = BinaryDecisionDiagram::from_formula(self);
+        let rhs = BinaryDecisionDiagram::from_formula(other);
+        let mut result = lhs.counterexample(&rhs)?;
+        for variable in self.variables().into_iter().chain(other.variables()) {
+            result.entry(variable).or_insert(false);
+        }
+        Some(result)
+    }
+}
+
 pub fn parse(code: &str) -> IResult<&str, Expression> {
     alt((
         map(or::parse, Expression::Or),
@@ -73,4 +172,86 @@ mod tests {
         assert!(result.contains("c"));
         assert!(!result.contains("d"));
     }
+
+    #[test]
+    fn test_satisfiability() {
+        let formula = parse("a&!b|c").unwrap().1;
+        assert!(formula.is_satisfiable());
+        assert!(!formula.is_tautology());
+        assert!(!formula.is_contradiction());
+        let assignment = formula.any_sat().unwrap();
+        assert_eq!(assignment.len(), 3);
+        assert_eq!(formula.eval(&assignment), true);
+
+        let formula = parse("!(a&!a)").unwrap().1;
+        assert!(formula.is_tautology());
+        assert_eq!(formula.any_sat().unwrap().len(), 1);
+
+        let formula = parse("(a|b)&!a&!b").unwrap().1;
+        assert!(formula.is_contradiction());
+        assert!(!formula.is_satisfiable());
+        assert_eq!(formula.any_sat(), None);
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let lhs = parse("!(a&b)|c").unwrap().1;
+        let rhs = parse("!a|!b|c").unwrap().1;
+        assert!(lhs.equivalent(&rhs));
+        assert_eq!(lhs.counterexample(&rhs), None);
+
+        let rhs = parse("!a|!b|c&(d|!d)").unwrap().1;
+        assert!(lhs.equivalent(&rhs));
+
+        let rhs = parse("!a|!b|c&d").unwrap().1;
+        assert!(!lhs.equivalent(&rhs));
+        let counterexample = lhs.counterexample(&rhs).unwrap();
+        assert_eq!(counterexample.len(), 4);
+        assert_ne!(lhs.eval(&counterexample), rhs.eval(&counterexample));
+    }
+
+    #[test]
+    fn test_probability() {
+        let formula = parse("a|b").unwrap().1;
+        let probabilities = [("a".to_string(), 0.5), ("b".to_string(), 0.5)]
+            .into_iter()
+            .collect();
+        assert_eq!(formula.probability(&probabilities), Ok(0.75));
+        assert_eq!(formula.weighted_count(&HashMap::new()), Ok(3.0));
+    }
+
+    #[test]
+    fn test_display() {
+        for code in ["!(a&b)|c", "a&(b|!!c)&1", "(a|b)&!(c|0)", "x"] {
+            let formula = parse(code).unwrap().1;
+            assert_eq!(formula.to_string(), code);
+        }
+        let formula = parse("!(a&b)|c&(d|!d)")
+            .unwrap()
+            .1
+            .remove_irrelevant_variables();
+        assert_eq!(formula.to_string(), "!(a&b)|c");
+    }
+
+    #[test]
+    fn test_remove_irrelevant_variables() {
+        let formula = parse("a|(b&!b)").unwrap().1;
+        assert_eq!(formula.support(), ["a".to_string()].into_iter().collect());
+        assert_eq!(
+            formula.remove_irrelevant_variables(),
+            Expression::Atom(Atom::Variable("a".to_string()))
+        );
+        let formula = parse("!(a&b)|c&(d|!d)").unwrap().1;
+        assert_eq!(
+            formula.remove_irrelevant_variables(),
+            parse("!(a&b)|c").unwrap().1
+        );
+        let formula = parse("(b&!b)|!(c|!c)").unwrap().1;
+        assert_eq!(
+            formula.remove_irrelevant_variables(),
+            Expression::Atom(Atom::Const(false))
+        );
+        let formula = parse("a&(b|c)").unwrap().1;
+        assert_eq!(formula.remove_irrelevant_variables(), formula);
+    }
 }
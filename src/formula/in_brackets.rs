@@ -1,5 +1,6 @@
 use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::fmt;
 
 use nom::bytes::complete::tag;
 use nom::combinator::map;
@@ -8,11 +9,19 @@ use nom::IResult;
 
 use super::expression;
 use super::Expression;
-use crate::{ContainVariable, Evaluable};
+use crate::binary_decision_diagram::Manager;
+use crate::{Assign, ContainVariable, Evaluable, SyntaxTree, ToBinaryDecisionDiagram};
+use petgraph::stable_graph::NodeIndex;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct InBrackets(pub Box<Expression>);
 
+impl fmt::Display for InBrackets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({})", self.0)
+    }
+}
+
 impl Evaluable for InBrackets {
     fn eval(&self, ctx: &HashMap<String, bool>) -> bool {
         self.0.eval(ctx)
@@ -25,6 +34,25 @@ impl ContainVariable for InBrackets {
     }
 }
 
+impl ToBinaryDecisionDiagram for InBrackets {
+    fn to_binary_decision_diagram(&self, manager: &mut Manager) -> NodeIndex {
+        self.0.to_binary_decision_diagram(manager)
+    }
+}
+
+// Brackets are added back by the operators which need them.
+impl Assign for InBrackets {
+    fn assign(&self, ctx: &HashMap<String, bool>) -> Expression {
+        self.0.assign(ctx)
+    }
+}
+
+impl SyntaxTree for InBrackets {
+    fn operands(&self) -> Vec<&dyn SyntaxTree> {
+        vec![&self.0]
+    }
+}
+
 pub fn parse(code: &str) -> IResult<&str, InBrackets> {
     map(
         delimited(tag("("), expression::parse, tag(")")),
@@ -1,4 +1,5 @@
 use std::collections::{BTreeSet, HashMap};
+use std::fmt;
 
 use enum_dispatch::enum_dispatch;
 use nom::branch::alt;
@@ -9,11 +10,20 @@ use nom::IResult;
 
 use super::atom::Atom;
 use super::in_brackets::InBrackets;
+use super::Expression;
 use super::{atom, in_brackets};
 
-use crate::{ContainVariable, Evaluable};
+use crate::binary_decision_diagram::Manager;
+use crate::{Assign, ContainVariable, Evaluable, SyntaxTree, ToBinaryDecisionDiagram};
+use petgraph::stable_graph::NodeIndex;
 
-#[enum_dispatch(Evaluable, ContainVariable)]
+#[enum_dispatch(
+    Evaluable,
+    ContainVariable,
+    ToBinaryDecisionDiagram,
+    SyntaxTree,
+    Assign
+)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum NotOperand {
     Atom,
@@ -21,9 +31,36 @@ pub(crate) enum NotOperand {
     Not(Box<Not>),
 }
 
+impl From<Expression> for NotOperand {
+    fn from(expression: Expression) -> Self {
+        match expression {
+            Expression::Atom(x) => NotOperand::Atom(x),
+            Expression::InBrackets(x) => NotOperand::InBrackets(x),
+            Expression::Not(x) => NotOperand::Not(Box::new(x)),
+            x => NotOperand::InBrackets(InBrackets(Box::new(x))),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Not(pub(crate) NotOperand);
 
+impl fmt::Display for NotOperand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotOperand::Atom(x) => x.fmt(f),
+            NotOperand::InBrackets(x) => x.fmt(f),
+            NotOperand::Not(x) => x.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Not {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "!{}", self.0)
+    }
+}
+
 impl Evaluable for Not {
     fn eval(&self, ctx: &HashMap<String, bool>) -> bool {
         !self.0.eval(ctx)
@@ -36,6 +73,29 @@ impl ContainVariable for Not {
     }
 }
 
+impl ToBinaryDecisionDiagram for Not {
+    fn to_binary_decision_diagram(&self, manager: &mut Manager) -> NodeIndex {
+        let operand = self.0.to_binary_decision_diagram(manager);
+        manager.not(operand)
+    }
+}
+
+impl Assign for Not {
+    fn assign(&self, ctx: &HashMap<String, bool>) -> Expression {
+        let operand = self.0.assign(ctx);
+        match operand.constant_value() {
+            Some(c) => Atom::Const(!c).into(),
+            None => Not(operand.into()).into(),
+        }
+    }
+}
+
+impl SyntaxTree for Not {
+    fn operands(&self) -> Vec<&dyn SyntaxTree> {
+        vec![&self.0]
+    }
+}
+
 pub fn parse(code: &str) -> IResult<&str, Not> {
     preceded(
         tag("!"),
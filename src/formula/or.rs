@@ -1,12 +1,14 @@
 use std::collections::{BTreeSet, HashMap};
-use std::ops;
+use std::{fmt, ops};
 
 use super::{and, atom, in_brackets, not};
+use crate::binary_decision_diagram::Manager;
 use crate::formula::and::And;
 use crate::formula::atom::Atom;
 use crate::formula::in_brackets::InBrackets;
 use crate::formula::not::Not;
-use crate::{ContainVariable, Evaluable};
+use crate::formula::Expression;
+use crate::{Assign, ContainVariable, Evaluable, SyntaxTree, ToBinaryDecisionDiagram};
 use enum_dispatch::enum_dispatch;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
@@ -14,8 +16,15 @@ use nom::combinator::map;
 use nom::multi::fold_many0;
 use nom::sequence::preceded;
 use nom::IResult;
+use petgraph::stable_graph::NodeIndex;
 
-#[enum_dispatch(Evaluable, ContainVariable)]
+#[enum_dispatch(
+    Evaluable,
+    ContainVariable,
+    ToBinaryDecisionDiagram,
+    SyntaxTree,
+    Assign
+)]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum OrOperand {
     Atom,
@@ -45,9 +54,40 @@ fn parse_higher_priority_operand(code: &str) -> IResult<&str, OrOperand> {
     ))(code)
 }
 
+impl From<Expression> for OrOperand {
+    fn from(expression: Expression) -> Self {
+        match expression {
+            Expression::Atom(x) => OrOperand::Atom(x),
+            Expression::InBrackets(x) => OrOperand::InBrackets(x),
+            Expression::Not(x) => OrOperand::Not(x),
+            Expression::And(x) => OrOperand::And(x),
+            Expression::Or(x) => OrOperand::Or(Box::new(x)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Or(pub(crate) OrOperand, pub(crate) OrOperand);
 
+impl fmt::Display for OrOperand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrOperand::Atom(x) => x.fmt(f),
+            OrOperand::InBrackets(x) => x.fmt(f),
+            OrOperand::Not(x) => x.fmt(f),
+            OrOperand::And(x) => x.fmt(f),
+            OrOperand::Or(x) => x.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Or {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Or(lhs, rhs) = self;
+        write!(f, "{lhs}|{rhs}")
+    }
+}
+
 impl Evaluable for Or {
     fn eval(&self, ctx: &HashMap<String, bool>) -> bool {
         let Or(lhs, rhs) = self;
@@ -62,6 +102,36 @@ impl ContainVariable for Or {
     }
 }
 
+impl ToBinaryDecisionDiagram for Or {
+    fn to_binary_decision_diagram(&self, manager: &mut Manager) -> NodeIndex {
+        let Or(lhs, rhs) = self;
+        let lhs = lhs.to_binary_decision_diagram(manager);
+        let rhs = rhs.to_binary_decision_diagram(manager);
+        manager.apply(|lhs, rhs| lhs || rhs, lhs, rhs)
+    }
+}
+
+impl Assign for Or {
+    fn assign(&self, ctx: &HashMap<String, bool>) -> Expression {
+        let Or(lhs, rhs) = self;
+        let lhs = lhs.assign(ctx);
+        let rhs = rhs.assign(ctx);
+        match (lhs.constant_value(), rhs.constant_value()) {
+            (Some(true), _) | (_, Some(true)) => Atom::Const(true).into(),
+            (Some(false), _) => rhs,
+            (_, Some(false)) => lhs,
+            _ => Or(lhs.into(), rhs.into()).into(),
+        }
+    }
+}
+
+impl SyntaxTree for Or {
+    fn operands(&self) -> Vec<&dyn SyntaxTree> {
+        let Or(lhs, rhs) = self;
+        vec![lhs, rhs]
+    }
+}
+
 pub fn parse(code: &str) -> IResult<&str, Or> {
     let (rest, first) = parse_higher_priority_operand(code)?;
     let (rest, second) = preceded(tag("|"), parse_higher_priority_operand)(rest)?;
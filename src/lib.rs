@@ -7,11 +7,13 @@ mod binary_decision_diagram;
 #[allow(clippy::bool_assert_comparison)]
 mod formula;
 
+use binary_decision_diagram::Manager;
 use enum_dispatch::enum_dispatch;
 use formula::and::AndOperand;
 use formula::not::NotOperand;
 use formula::or::OrOperand;
 use formula::*;
+use petgraph::stable_graph::NodeIndex;
 use std::collections::{BTreeSet, HashMap};
 #[enum_dispatch]
 trait Evaluable {
@@ -41,6 +43,57 @@ where
     }
 }
 
+#[enum_dispatch]
+trait ToBinaryDecisionDiagram {
+    fn to_binary_decision_diagram(&self, manager: &mut Manager) -> NodeIndex;
+}
+
+impl<T> ToBinaryDecisionDiagram for Box<T>
+where
+    T: ToBinaryDecisionDiagram,
+{
+    fn to_binary_decision_diagram(&self, manager: &mut Manager) -> NodeIndex {
+        Box::as_ref(self).to_binary_decision_diagram(manager)
+    }
+}
+
+#[enum_dispatch]
+trait Assign {
+    // Replaces the variables in `ctx` with constants and folds the constants away.
+    fn assign(&self, ctx: &HashMap<String, bool>) -> Expression;
+}
+
+impl<T> Assign for Box<T>
+where
+    T: Assign,
+{
+    fn assign(&self, ctx: &HashMap<String, bool>) -> Expression {
+        Box::as_ref(self).assign(ctx)
+    }
+}
+
+#[enum_dispatch]
+trait SyntaxTree {
+    fn operands(&self) -> Vec<&dyn SyntaxTree>;
+
+    fn variable_name(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl<T> SyntaxTree for Box<T>
+where
+    T: SyntaxTree,
+{
+    fn operands(&self) -> Vec<&dyn SyntaxTree> {
+        Box::as_ref(self).operands()
+    }
+
+    fn variable_name(&self) -> Option<&str> {
+        Box::as_ref(self).variable_name()
+    }
+}
+
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
 // allocator.
 #[cfg(feature = "wee_alloc")]